@@ -0,0 +1,63 @@
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use space_invaders::{Direction, Effect, Logic, Team};
+use std::time::Duration;
+
+const TICK: Duration = Duration::from_millis(50);
+
+// A full tick, the way the game runs it minus the player's input
+fn tick(logic: &mut Logic) {
+    logic.fade();
+    logic.generate();
+    logic.shift();
+    logic.handle();
+    logic.hit();
+    logic.events_reset();
+    logic.advance(TICK);
+}
+
+// Longer than the slowest reload, Triple's
+const RELOAD: Duration = Duration::from_millis(1600);
+
+// A board of `rows` by `cols` with the player sweeping across it from the left wall,
+// firing Double and Triple shots from every column and stacking an Ultra beam every
+// few columns
+fn barrage(rows: i32, cols: i32) -> Logic {
+    let mut logic = Logic::new(rows, cols).expect("The board fits the first wave");
+    let mut level = 0;
+    logic.level_up(&mut level);
+    for _ in 0..cols {
+        logic.player_move(&Direction::Left);
+    }
+    let mut shots = 0;
+    for col in 0..cols {
+        // Granted again on every shot, since the reloads add up to more than they last
+        logic.grant(Effect::Double);
+        logic.grant(Effect::Triple);
+        if col % 4 == 0 {
+            logic.grant(Effect::Ultra);
+        }
+        logic.advance(RELOAD);
+        logic.player_shoot();
+        shots += 1;
+        logic.player_move(&Direction::Right);
+    }
+    let bullets = logic.entities().bullets(Team::Player).count();
+    assert!(
+        bullets >= 3 * shots,
+        "Only {bullets} bullets after {shots} shots"
+    );
+    logic
+}
+
+fn bench(c: &mut Criterion) {
+    for (rows, cols) in [(24, 40), (100, 200), (200, 400)] {
+        let logic = barrage(rows, cols);
+        let name = format!("tick {rows}x{cols}, {} entities", logic.entities().len());
+        c.bench_function(&name, |b| {
+            b.iter_batched_ref(|| logic.clone(), tick, BatchSize::SmallInput)
+        });
+    }
+}
+
+criterion_group!(benches, bench);
+criterion_main!(benches);
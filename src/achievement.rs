@@ -0,0 +1,36 @@
+use std::fmt;
+
+/// A feat earned during a game, reported by [`Logic::feats`](crate::Logic::feats).
+#[derive(Clone, Copy, Eq, Hash, PartialEq, Debug)]
+pub enum Achievement {
+    /// Clear a wave firing only while [`Effect::Pierce`](crate::Effect::Pierce) is active.
+    Needlework,
+    /// Kill the boss while [`Effect::Jump`](crate::Effect::Jump) is active.
+    Leapfrog,
+    /// Clear a wave without firing a single shot.
+    Pacifist,
+    /// Wipe out a wave with [`Effect::Kamizake`](crate::Effect::Kamizake).
+    Banzai,
+}
+
+impl fmt::Display for Achievement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl Achievement {
+    const ALL: [Achievement; 4] = [
+        Achievement::Needlework,
+        Achievement::Leapfrog,
+        Achievement::Pacifist,
+        Achievement::Banzai,
+    ];
+
+    /// The achievement written as `name`, if any.
+    pub fn parse(name: &str) -> Option<Achievement> {
+        Achievement::ALL
+            .into_iter()
+            .find(|achievement| achievement.to_string() == name)
+    }
+}
@@ -0,0 +1,77 @@
+use crate::save;
+use space_invaders::Achievement;
+use std::{
+    collections::{HashSet, VecDeque},
+    fs, io,
+    path::PathBuf,
+    time::Duration,
+};
+
+const TOAST_DURATION: Duration = Duration::from_secs(3);
+const ACHIEVEMENTS_FILE: &str = "achievements";
+
+pub struct Achievements {
+    unlocked: HashSet<Achievement>,
+    toasts: VecDeque<Achievement>,
+    // How long the front toast has been up, in game time so that it waits out pauses
+    toast_time: Duration,
+    path: Option<PathBuf>,
+}
+
+impl Achievements {
+    pub fn load() -> Self {
+        let path = save::data_dir().map(|dir| dir.join(ACHIEVEMENTS_FILE));
+        let mut unlocked = HashSet::new();
+        if let Some(contents) = path.as_ref().and_then(|path| fs::read_to_string(path).ok()) {
+            unlocked.extend(contents.lines().filter_map(Achievement::parse));
+        }
+        Self {
+            unlocked,
+            toasts: VecDeque::new(),
+            toast_time: Duration::ZERO,
+            path,
+        }
+    }
+
+    fn save(&self) -> io::Result<()> {
+        if let Some(path) = &self.path {
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            let mut contents = String::new();
+            for achievement in &self.unlocked {
+                contents += &format!("{achievement}\n");
+            }
+            fs::write(path, contents)?;
+        }
+        Ok(())
+    }
+
+    pub fn unlock(&mut self, achievement: Achievement) {
+        if self.unlocked.insert(achievement) {
+            if self.toasts.is_empty() {
+                self.toast_time = Duration::ZERO;
+            }
+            self.toasts.push_back(achievement);
+            // Losing an achievement isn't worth interrupting the game over
+            self.save().ok();
+        }
+    }
+
+    // Lets a tick's worth of time go by, taking the front toast down once it has been up
+    // long enough
+    pub fn advance(&mut self, elapsed: Duration) {
+        if self.toasts.is_empty() {
+            return;
+        }
+        self.toast_time += elapsed;
+        if self.toast_time >= TOAST_DURATION {
+            self.toasts.pop_front();
+            self.toast_time = Duration::ZERO;
+        }
+    }
+
+    pub fn toast(&self) -> Option<&Achievement> {
+        self.toasts.front()
+    }
+}
@@ -0,0 +1,231 @@
+use crate::theme;
+use ncurses::*;
+use space_invaders::role::Role;
+use unicode_width::UnicodeWidthChar;
+
+// How a cell is drawn: the role's theme style, plus any extra attributes
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Pen {
+    pub role: Option<Role>,
+    pub attributes: attr_t,
+}
+
+impl Pen {
+    pub fn new(role: Role) -> Self {
+        Self {
+            role: Some(role),
+            attributes: A_NORMAL(),
+        }
+    }
+
+    pub fn plain() -> Self {
+        Self {
+            role: None,
+            attributes: A_NORMAL(),
+        }
+    }
+
+    pub fn with(self, attributes: attr_t) -> Self {
+        Self {
+            attributes: self.attributes | attributes,
+            ..self
+        }
+    }
+
+    fn attr(&self) -> attr_t {
+        match self.role {
+            Some(role) => theme::attributes(role) | self.attributes,
+            None => self.attributes,
+        }
+    }
+}
+
+// The right half of a wide glyph is left empty
+#[derive(Clone, PartialEq, Debug)]
+pub struct Cell {
+    pub glyph: String,
+    pub pen: Pen,
+}
+
+impl Cell {
+    fn blank() -> Self {
+        Self {
+            glyph: " ".to_string(),
+            pen: Pen::plain(),
+        }
+    }
+
+    fn is_wide(&self) -> bool {
+        self.glyph.chars().next().and_then(|c| c.width()) == Some(2)
+    }
+}
+
+// A frame buffer for a window, drawn into from scratch every frame
+#[derive(Clone, PartialEq, Debug)]
+pub struct Canvas {
+    rows: i32,
+    cols: i32,
+    cells: Vec<Cell>,
+}
+
+impl Canvas {
+    pub fn new(rows: i32, cols: i32) -> Self {
+        Self {
+            rows,
+            cols,
+            cells: vec![Cell::blank(); (rows * cols).max(0) as usize],
+        }
+    }
+
+    pub fn rows(&self) -> i32 {
+        self.rows
+    }
+
+    pub fn cols(&self) -> i32 {
+        self.cols
+    }
+
+    pub fn cell(&self, pos: (i32, i32)) -> &Cell {
+        &self.cells[self.index(pos)]
+    }
+
+    // Copies a cell as it is, for canvases put together cell by cell
+    pub fn put(&mut self, pos: (i32, i32), cell: Cell) {
+        let index = self.index(pos);
+        self.cells[index] = cell;
+    }
+
+    // Copies another canvas in from `row` down, clipping whatever doesn't fit
+    pub fn paste(&mut self, canvas: &Canvas, row: i32) {
+        for y in 0..canvas.rows() {
+            for x in 0..canvas.cols() {
+                if self.contains((row + y, x)) {
+                    self.put((row + y, x), canvas.cell((y, x)).clone());
+                }
+            }
+        }
+    }
+
+    fn index(&self, pos: (i32, i32)) -> usize {
+        (pos.0 * self.cols + pos.1) as usize
+    }
+
+    fn contains(&self, pos: (i32, i32)) -> bool {
+        pos.0 >= 0 && pos.0 < self.rows && pos.1 >= 0 && pos.1 < self.cols
+    }
+
+    fn set(&mut self, pos: (i32, i32), glyph: String, pen: Pen) {
+        // Never leave half of a wide glyph behind
+        if self.cell(pos).glyph.is_empty() && pos.1 > 0 {
+            let left = self.index((pos.0, pos.1 - 1));
+            self.cells[left] = Cell::blank();
+        }
+        if self.cell(pos).is_wide() && pos.1 + 1 < self.cols {
+            let right = self.index((pos.0, pos.1 + 1));
+            self.cells[right] = Cell::blank();
+        }
+        let index = self.index(pos);
+        self.cells[index] = Cell { glyph, pen };
+    }
+
+    // Writes a single line of text, clipping whatever doesn't fit
+    pub fn write(&mut self, pos: (i32, i32), text: &str, pen: Pen) {
+        let (row, mut col) = pos;
+        for c in text.chars() {
+            let width = c.width().unwrap_or(0) as i32;
+            if width == 0 {
+                // Combining marks belong to the glyph before them
+                if self.contains((row, col - 1)) {
+                    let index = self.index((row, col - 1));
+                    self.cells[index].glyph.push(c);
+                }
+                continue;
+            }
+            if self.contains((row, col)) && col + width <= self.cols {
+                self.set((row, col), c.to_string(), pen);
+                if width == 2 {
+                    self.set((row, col + 1), String::new(), pen);
+                }
+            }
+            col += width;
+        }
+    }
+
+    pub fn border(&mut self) {
+        let (bottom, right) = (self.rows - 1, self.cols - 1);
+        for col in 1..right {
+            self.write((0, col), "─", Pen::plain());
+            self.write((bottom, col), "─", Pen::plain());
+        }
+        for row in 1..bottom {
+            self.write((row, 0), "│", Pen::plain());
+            self.write((row, right), "│", Pen::plain());
+        }
+        self.write((0, 0), "┌", Pen::plain());
+        self.write((0, right), "┐", Pen::plain());
+        self.write((bottom, 0), "└", Pen::plain());
+        self.write((bottom, right), "┘", Pen::plain());
+    }
+}
+
+// Puts canvases on a window, only touching the cells that changed since the last one
+pub struct Screen {
+    window: WINDOW,
+    shown: Option<Canvas>,
+}
+
+impl Screen {
+    pub fn new(window: WINDOW) -> Self {
+        Self {
+            window,
+            shown: None,
+        }
+    }
+
+    // Something else drew over the window, so all of it has to be put back
+    pub fn touch(&self) {
+        touchwin(self.window);
+    }
+
+    pub fn canvas(&self) -> Canvas {
+        Canvas::new(getmaxy(self.window), getmaxx(self.window))
+    }
+
+    // Stages the canvas, which only reaches the terminal on the next doupdate
+    pub fn present(&mut self, canvas: Canvas) {
+        let shown = self
+            .shown
+            .as_ref()
+            .filter(|shown| shown.rows() == canvas.rows() && shown.cols() == canvas.cols());
+        for row in 0..canvas.rows() {
+            for col in 0..canvas.cols() {
+                let cell = canvas.cell((row, col));
+                let unchanged = shown.is_some_and(|shown| shown.cell((row, col)) == cell);
+                if unchanged || cell.glyph.is_empty() {
+                    continue;
+                }
+                wattrset(self.window, cell.pen.attr());
+                match line(&cell.glyph) {
+                    Some(line) => mvwaddch(self.window, row, col, line),
+                    None => mvwaddstr(self.window, row, col, &cell.glyph),
+                };
+            }
+        }
+        wattrset(self.window, A_NORMAL());
+        wnoutrefresh(self.window);
+        self.shown = Some(canvas);
+    }
+}
+
+// Box drawing goes through ncurses so that it works on any terminal
+fn line(glyph: &str) -> Option<chtype> {
+    match glyph {
+        "─" => Some(ACS_HLINE()),
+        "│" => Some(ACS_VLINE()),
+        "┌" => Some(ACS_ULCORNER()),
+        "┐" => Some(ACS_URCORNER()),
+        "└" => Some(ACS_LLCORNER()),
+        "┘" => Some(ACS_LRCORNER()),
+        _ => None,
+    }
+}
@@ -0,0 +1,380 @@
+use space_invaders::{Difficulty, Error, COLS, LINES};
+use std::fs;
+
+const MAX_LIVES: i8 = 9;
+
+pub const USAGE: &str = "\
+Usage: space-invaders [OPTIONS]
+
+Modes, one at most:
+      --bot               Let the autopilot play
+      --host PORT         Wait for a versus opponent on PORT
+      --join ADDR         Play versus the game hosted at ADDR
+      --spectate ADDR     Watch the game broadcast at ADDR
+      --headless          Simulate with the autopilot, without a terminal, and print
+                          the final stats
+
+Game:
+      --seed N            Roll the dice from N, so the same moves play out the same
+      --level N           Start on level N [default: 1]
+      --lives N           Start with, and never hold more than, N ships [default: 3]
+      --size ROWSxCOLS    Size of the board, borders included [default: 24x40]
+      --difficulty NAME   easy, normal or hard [default: normal]
+      --ticks N           Stop a headless simulation after N ticks
+      --scripts DIR       Load power-ups and level behaviors from the .rhai files in DIR
+
+Display and input:
+      --theme NAME|FILE   Built-in theme or theme file [default: default]
+      --no-color          Draw in monochrome, as does setting NO_COLOR
+      --keymap FILE       Rebind keys with lines such as `shoot z`
+      --broadcast PORT    Let spectators watch on PORT
+
+Sessions:
+      --record FILE       Save the run's setup and every move to FILE
+      --replay FILE       Play back a recording, starting from its setup
+      --config FILE       Read options from FILE, one per line such as `seed 42`.
+                          Options on the command line take precedence
+  -h, --help              Print this help
+";
+
+// Flags that come with a value, the rest are switches
+const VALUED: [&str; 16] = [
+    "--host",
+    "--join",
+    "--spectate",
+    "--seed",
+    "--level",
+    "--lives",
+    "--size",
+    "--difficulty",
+    "--ticks",
+    "--scripts",
+    "--theme",
+    "--keymap",
+    "--broadcast",
+    "--record",
+    "--replay",
+    "--config",
+];
+const SWITCHES: [&str; 5] = ["--bot", "--headless", "--no-color", "--help", "-h"];
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum Mode {
+    Play,
+    Bot,
+    Host(u16),
+    Join(String),
+    Spectate(String),
+    Headless,
+}
+
+#[derive(Clone, Debug)]
+pub struct Options {
+    pub mode: Mode,
+    pub seed: Option<u64>,
+    pub level: i32,
+    pub lives: i8,
+    pub size: (i32, i32),
+    pub difficulty: Difficulty,
+    pub ticks: Option<usize>,
+    pub scripts: Option<String>,
+    pub theme: String,
+    pub no_color: bool,
+    pub keymap: Option<String>,
+    pub broadcast: Option<u16>,
+    pub record: Option<String>,
+    pub replay: Option<String>,
+    pub help: bool,
+    // Whether any of the game options were given, which rules out resuming a saved run
+    pub custom: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            mode: Mode::Play,
+            seed: None,
+            level: 1,
+            lives: 3,
+            size: (LINES, COLS),
+            difficulty: Difficulty::Normal,
+            ticks: None,
+            scripts: None,
+            theme: "default".to_string(),
+            no_color: false,
+            keymap: None,
+            broadcast: None,
+            record: None,
+            replay: None,
+            help: false,
+            custom: false,
+        }
+    }
+}
+
+impl Options {
+    // Options from the config file, if any, come first so that the command line overrides them
+    pub fn parse(args: &[String]) -> Result<Options, Error> {
+        let mut options = Options::default();
+        if let Some(path) = config_path(args)? {
+            let config = config(path)?;
+            options.apply(&config, Some(path))?;
+        }
+        options.apply(args, None)?;
+        options.check()?;
+        Ok(options)
+    }
+
+    // Reads pairs of flags and values, where `config` is the file they came from
+    fn apply(&mut self, args: &[String], config: Option<&str>) -> Result<(), Error> {
+        let mut args = args.iter();
+        let mut mode = None;
+        while let Some(flag) = args.next() {
+            let flag = flag.as_str();
+            if SWITCHES.contains(&flag) {
+                match flag {
+                    "--bot" => self.set_mode(&mut mode, flag, Mode::Bot)?,
+                    "--headless" => self.set_mode(&mut mode, flag, Mode::Headless)?,
+                    "--no-color" => self.no_color = true,
+                    _ => self.help = true,
+                }
+                continue;
+            }
+            if !VALUED.contains(&flag) {
+                return Err(usage(format!("Unknown option {flag}")));
+            }
+            let Some(value) = args.next() else {
+                return Err(usage(format!("{flag} takes a value")));
+            };
+            let value = value.as_str();
+            match flag {
+                "--host" => self.set_mode(&mut mode, flag, Mode::Host(port(value)?))?,
+                "--join" => self.set_mode(&mut mode, flag, Mode::Join(value.to_string()))?,
+                "--spectate" => {
+                    self.set_mode(&mut mode, flag, Mode::Spectate(value.to_string()))?
+                }
+                "--seed" => self.seed = Some(number(flag, value)?),
+                "--level" => self.level = number(flag, value)?,
+                "--lives" => self.lives = number(flag, value)?,
+                "--size" => self.size = size(value)?,
+                "--difficulty" => {
+                    self.difficulty = Difficulty::parse(value).ok_or_else(|| {
+                        usage(format!(
+                            "Unknown difficulty {value}, pick easy, normal or hard"
+                        ))
+                    })?
+                }
+                "--ticks" => self.ticks = Some(number(flag, value)?),
+                "--scripts" => self.scripts = Some(value.to_string()),
+                "--theme" => self.theme = value.to_string(),
+                "--keymap" => self.keymap = Some(value.to_string()),
+                "--broadcast" => self.broadcast = Some(port(value)?),
+                "--record" => self.record = Some(value.to_string()),
+                "--replay" => self.replay = Some(value.to_string()),
+                // Read before anything else, and only from the command line
+                _ => {
+                    if let Some(path) = config {
+                        return Err(usage(format!("{flag} can't be used in {path}")));
+                    }
+                }
+            }
+            if matches!(
+                flag,
+                "--seed" | "--level" | "--lives" | "--size" | "--difficulty"
+            ) {
+                self.custom = true;
+            }
+        }
+        Ok(())
+    }
+
+    // Modes exclude each other, though the command line may replace the config file's
+    fn set_mode(
+        &mut self,
+        chosen: &mut Option<String>,
+        flag: &str,
+        mode: Mode,
+    ) -> Result<(), Error> {
+        match chosen {
+            Some(other) if other != flag => {
+                return Err(usage(format!("{other} and {flag} can't be used together")))
+            }
+            _ => *chosen = Some(flag.to_string()),
+        }
+        self.mode = mode;
+        Ok(())
+    }
+
+    fn check(&self) -> Result<(), Error> {
+        if self.level < 1 {
+            return Err(usage("--level starts from 1".to_string()));
+        }
+        if !(1..=MAX_LIVES).contains(&self.lives) {
+            return Err(usage(format!("--lives takes 1 to {MAX_LIVES} ships")));
+        }
+        if self.ticks.is_some() && self.mode != Mode::Headless {
+            return Err(usage("--ticks only applies to --headless".to_string()));
+        }
+        if self.record.is_some() && self.replay.is_some() {
+            return Err(usage(
+                "--record and --replay can't be used together".to_string(),
+            ));
+        }
+        let networked = matches!(self.mode, Mode::Host(_) | Mode::Join(_) | Mode::Spectate(_));
+        if networked && (self.record.is_some() || self.replay.is_some()) {
+            // The opponent's garbage isn't part of the recording
+            return Err(usage(
+                "Versus games and spectating can't be recorded or replayed".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    // Whether to draw without colors, which NO_COLOR asks for whatever it is set to
+    pub fn monochrome(&self) -> bool {
+        self.no_color || std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty())
+    }
+}
+
+fn usage(message: String) -> Error {
+    Error::Usage(message)
+}
+
+fn port(port: &str) -> Result<u16, Error> {
+    port.parse().map_err(|_| Error::Port(port.to_string()))
+}
+
+fn number<T: std::str::FromStr>(flag: &str, value: &str) -> Result<T, Error> {
+    value
+        .parse()
+        .map_err(|_| usage(format!("{flag} takes a number, not {value}")))
+}
+
+fn size(value: &str) -> Result<(i32, i32), Error> {
+    let invalid = || {
+        usage(format!(
+            "--size takes ROWSxCOLS, such as 24x40, not {value}"
+        ))
+    };
+    let (rows, cols) = value.split_once('x').ok_or_else(invalid)?;
+    let rows = rows.parse().map_err(|_| invalid())?;
+    let cols = cols.parse().map_err(|_| invalid())?;
+    Ok((rows, cols))
+}
+
+fn config_path(args: &[String]) -> Result<Option<&str>, Error> {
+    let Some(index) = args.iter().position(|arg| arg == "--config") else {
+        return Ok(None);
+    };
+    match args.get(index + 1) {
+        Some(path) => Ok(Some(path)),
+        None => Err(usage("--config takes a value".to_string())),
+    }
+}
+
+// Lines of an option and maybe its value, without the dashes, turned into arguments
+fn config(path: &str) -> Result<Vec<String>, Error> {
+    let contents = fs::read_to_string(path).map_err(|e| Error::File {
+        path: path.to_string(),
+        reason: e.to_string(),
+    })?;
+    let mut args = vec![];
+    for line in contents.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (flag, value) = match line.split_once(char::is_whitespace) {
+            Some((flag, value)) => (flag, Some(value.trim())),
+            None => (line, None),
+        };
+        args.push(format!("--{}", flag.trim_start_matches('-')));
+        args.extend(value.map(str::to_string));
+    }
+    Ok(args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Mode, Options};
+    use space_invaders::{Difficulty, Error};
+    use std::fs;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(str::to_string).collect()
+    }
+
+    fn parse(line: &str) -> Result<Options, Error> {
+        Options::parse(&args(line))
+    }
+
+    // Writes `contents` to a config file named after the test
+    fn config(name: &str, contents: &str) -> String {
+        let path =
+            std::env::temp_dir().join(format!("space-invaders-{name}-{}.conf", std::process::id()));
+        fs::write(&path, contents).unwrap();
+        path.to_string_lossy().into_owned()
+    }
+
+    fn rejected(result: Result<Options, Error>, hint: &str) {
+        match result {
+            Err(Error::Usage(message)) => assert!(message.contains(hint), "{message}"),
+            Err(e) => panic!("expected a usage error, got {e}"),
+            Ok(_) => panic!("expected a usage error"),
+        }
+    }
+
+    #[test]
+    fn the_command_line_overrides_the_config() {
+        let path = config("override", "# tuned\nseed 7\ndifficulty hard\nbot\n");
+        let options = parse(&format!("--config {path} --difficulty easy"));
+        fs::remove_file(&path).unwrap();
+        let options = options.unwrap();
+        assert_eq!(options.seed, Some(7));
+        assert_eq!(options.difficulty, Difficulty::Easy);
+        assert_eq!(options.mode, Mode::Bot);
+    }
+
+    #[test]
+    fn modes_exclude_each_other() {
+        rejected(parse("--bot --host 4000"), "can't be used together");
+        assert_eq!(parse("--bot --bot").unwrap().mode, Mode::Bot);
+    }
+
+    #[test]
+    fn configs_cant_name_another_config() {
+        let path = config("nested", "config other.conf\n");
+        let options = parse(&format!("--config {path}"));
+        fs::remove_file(&path).unwrap();
+        rejected(options, "--config can't be used in");
+    }
+
+    #[test]
+    fn ticks_need_headless() {
+        rejected(parse("--ticks 10"), "--ticks only applies to --headless");
+        assert_eq!(parse("--headless --ticks 10").unwrap().ticks, Some(10));
+    }
+
+    #[test]
+    fn sizes_take_rows_and_columns() {
+        rejected(parse("--size 24x"), "--size takes ROWSxCOLS");
+        rejected(parse("--size 24"), "--size takes ROWSxCOLS");
+        assert_eq!(parse("--size 30x50").unwrap().size, (30, 50));
+    }
+
+    #[test]
+    fn only_game_options_are_custom() {
+        let display = "--no-color --theme default --headless --ticks 5 --scripts scripts";
+        assert!(!parse(display).unwrap().custom);
+        let game = [
+            "--seed 1",
+            "--level 2",
+            "--lives 5",
+            "--size 30x50",
+            "--difficulty hard",
+        ];
+        for option in game {
+            assert!(parse(option).unwrap().custom, "{option}");
+        }
+    }
+}
@@ -0,0 +1,214 @@
+use crate::keymap::Keymap;
+use ncurses::{nodelay, wgetch, ERR, WINDOW};
+use serde::{Deserialize, Serialize};
+use space_invaders::{entity::Team, logic::Logic, object::Object};
+
+// How many rows above the player a laser still counts as a threat
+const DANGER_RANGE: i32 = 3;
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub enum Action {
+    Idle,
+    Shoot,
+    Left,
+    Right,
+    Pause,
+    Quit,
+}
+
+impl Action {
+    const ALL: [Action; 6] = [
+        Action::Idle,
+        Action::Shoot,
+        Action::Left,
+        Action::Right,
+        Action::Pause,
+        Action::Quit,
+    ];
+
+    // As written in key maps, such as `shoot`
+    pub fn parse(name: &str) -> Option<Action> {
+        Action::ALL
+            .into_iter()
+            .find(|action| format!("{action:?}").eq_ignore_ascii_case(name))
+    }
+}
+
+pub trait PlayerController {
+    // Called once per update with a read-only view of the game,
+    // returning everything the player did since the last call, oldest first
+    fn act(&mut self, logic: &Logic) -> Vec<Action>;
+}
+
+pub struct Keyboard {
+    window: WINDOW,
+    keymap: Keymap,
+}
+
+impl Keyboard {
+    pub fn new(window: WINDOW, keymap: Keymap) -> Self {
+        // Reading keys never holds up the game
+        nodelay(window, true);
+        Self { window, keymap }
+    }
+}
+
+impl PlayerController for Keyboard {
+    fn act(&mut self, _logic: &Logic) -> Vec<Action> {
+        let mut actions = vec![];
+        loop {
+            let input = wgetch(self.window);
+            if input == ERR {
+                return actions;
+            }
+            actions.push(self.keymap.action(input));
+        }
+    }
+}
+
+// Dodges incoming lasers, then lines up with power-ups and, failing that, enemies
+pub struct Bot;
+
+impl Bot {
+    fn is_dangerous(logic: &Logic, pos: (i32, i32)) -> bool {
+        logic.entities().bullets(Team::Enemy).any(|laser| {
+            let (row, col) = laser.pos();
+            col == pos.1 && pos.0 - row >= 0 && pos.0 - row <= DANGER_RANGE
+        })
+    }
+
+    fn target(logic: &Logic, pos: (i32, i32)) -> Option<i32> {
+        let distance = |col: i32| (col - pos.1).abs();
+        let power = logic
+            .entities()
+            .iter()
+            .filter(|entity| entity.effect().is_some())
+            .map(|power| power.pos().1);
+        let enemy = logic.enemies().map(|enemy| enemy.pos().1);
+        power
+            .min_by_key(|col| distance(*col))
+            .or_else(|| enemy.min_by_key(|col| distance(*col)))
+    }
+
+    fn decide(logic: &Logic) -> Action {
+        let pos = logic.player().pos();
+        let left = (pos.0, pos.1 - 1);
+        let right = (pos.0, pos.1 + 1);
+        let is_safe = |pos| logic.on_board(pos) && !Bot::is_dangerous(logic, pos);
+
+        if Bot::is_dangerous(logic, pos) {
+            return if is_safe(left) {
+                Action::Left
+            } else if is_safe(right) {
+                Action::Right
+            } else {
+                Action::Shoot
+            };
+        }
+
+        match Bot::target(logic, pos) {
+            Some(col) if col < pos.1 && is_safe(left) => Action::Left,
+            Some(col) if col > pos.1 && is_safe(right) => Action::Right,
+            Some(_) => Action::Shoot,
+            None => Action::Idle,
+        }
+    }
+}
+
+impl PlayerController for Bot {
+    fn act(&mut self, logic: &Logic) -> Vec<Action> {
+        vec![Bot::decide(logic)]
+    }
+}
+
+// The bot plays while the keyboard is only listened to for pausing and quitting
+pub struct Autopilot {
+    keyboard: Keyboard,
+    bot: Bot,
+}
+
+impl Autopilot {
+    pub fn new(keyboard: Keyboard) -> Self {
+        Self { keyboard, bot: Bot }
+    }
+}
+
+impl PlayerController for Autopilot {
+    fn act(&mut self, logic: &Logic) -> Vec<Action> {
+        let keys = self.keyboard.act(logic);
+        if keys.contains(&Action::Quit) {
+            vec![Action::Quit]
+        } else if keys.contains(&Action::Pause) {
+            vec![Action::Pause]
+        } else {
+            self.bot.act(logic)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Action, Bot, DANGER_RANGE};
+    use space_invaders::{entity::Team, logic::Logic, object::Object, Direction, COLS, LINES};
+    use std::time::Duration;
+
+    fn logic() -> Logic {
+        let mut logic = Logic::new(LINES, COLS).unwrap().with_seed(0);
+        let mut level = 0;
+        logic.level_up(&mut level);
+        logic
+    }
+
+    fn tick(logic: &mut Logic) {
+        logic.fade();
+        logic.generate();
+        logic.shift();
+        logic.handle();
+        logic.hit();
+        logic.events_reset();
+        logic.advance(Duration::from_millis(50));
+    }
+
+    // Lasers in the column, at most `range` rows above the given cell
+    fn lasers_over(logic: &Logic, pos: (i32, i32), range: i32) -> usize {
+        logic
+            .entities()
+            .bullets(Team::Enemy)
+            .filter(|laser| {
+                laser.pos().1 == pos.1 && (0..=range).contains(&(pos.0 - laser.pos().0))
+            })
+            .count()
+    }
+
+    #[test]
+    fn the_bot_dodges_a_laser_right_above_it() {
+        let mut logic = logic();
+        // The player stands still until a laser comes down on it
+        let mut ticks = 0;
+        while lasers_over(&logic, logic.player().pos(), 1) == 0 {
+            assert!(ticks < 2000, "No laser ever came down on the player");
+            tick(&mut logic);
+            ticks += 1;
+        }
+
+        let (row, col) = logic.player().pos();
+        let step = match Bot::decide(&logic) {
+            Action::Left => (row, col - 1),
+            Action::Right => (row, col + 1),
+            action => panic!("The bot stayed under the laser, {action:?} instead"),
+        };
+        assert_eq!(lasers_over(&logic, step, DANGER_RANGE), 0);
+    }
+
+    #[test]
+    fn the_bot_shoots_when_its_column_is_clear() {
+        let mut logic = logic();
+        let col = logic.enemies().map(|enemy| enemy.pos().1).min().unwrap();
+        while logic.player().pos().1 > col {
+            logic.player_move(&Direction::Left);
+        }
+
+        assert_eq!(lasers_over(&logic, logic.player().pos(), LINES), 0);
+        assert_eq!(Bot::decide(&logic), Action::Shoot);
+    }
+}
@@ -0,0 +1,62 @@
+//! How hard a game is.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// How hard the enemies push back, from how often they fire to how often power-ups
+/// turn up to help.
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub enum Difficulty {
+    /// Enemies fire less and power-ups turn up more often.
+    Easy,
+    /// The game as it was meant to be played.
+    #[default]
+    Normal,
+    /// Enemies fire more and power-ups are scarce.
+    Hard,
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl Difficulty {
+    /// Every difficulty, from the easiest.
+    pub const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard];
+
+    /// What it is called on the command line.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Normal => "normal",
+            Difficulty::Hard => "hard",
+        }
+    }
+
+    /// The difficulty called `name` on the command line, if any.
+    pub fn parse(name: &str) -> Option<Difficulty> {
+        Difficulty::ALL
+            .into_iter()
+            .find(|difficulty| difficulty.name() == name)
+    }
+
+    /// The odds of each enemy firing on any given tick.
+    pub fn fire_probability(&self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.03,
+            Difficulty::Normal => 0.05,
+            Difficulty::Hard => 0.08,
+        }
+    }
+
+    /// The odds of a power-up turning up on any given tick.
+    pub fn power_probability(&self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.1,
+            Difficulty::Normal => 0.08,
+            Difficulty::Hard => 0.05,
+        }
+    }
+}
@@ -1,17 +1,46 @@
+//! Which way things move on the board.
+
 use rand::{distributions::Standard, prelude::Distribution, Rng};
+use serde::{Deserialize, Serialize};
 
-#[derive(PartialEq, Clone, Copy)]
+/// A step to one of the eight neighbouring cells. Up is towards row zero.
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
 pub enum Direction {
+    /// One column to the left.
     Left,
+    /// One column to the right.
     Right,
+    /// One row up and one column to the left.
     LeftUp,
+    /// One row up and one column to the right.
     RightUp,
+    /// One row down and one column to the left.
     LeftDown,
+    /// One row down and one column to the right.
     RightDown,
+    /// One row up.
     Up,
+    /// One row down.
     Down,
 }
 
+impl Direction {
+    /// The step that undoes this one.
+    pub fn opposite(&self) -> Direction {
+        match self {
+            Direction::Up => Direction::Down,
+            Direction::Left => Direction::Right,
+            Direction::Right => Direction::Left,
+            Direction::Down => Direction::Up,
+            Direction::LeftDown => Direction::RightUp,
+            Direction::LeftUp => Direction::RightDown,
+            Direction::RightDown => Direction::LeftUp,
+            Direction::RightUp => Direction::LeftDown,
+        }
+    }
+}
+
+/// Random directions are the two upward diagonals, as fired by mind controlled enemies.
 impl Distribution<Direction> for Standard {
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Direction {
         match rng.gen_range(0..=1) {
@@ -0,0 +1,64 @@
+// Timers are stored as the milliseconds since they started
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::time::Duration;
+
+// Anything older than this has long expired
+const MAX_ELAPSED: Duration = Duration::from_secs(60);
+
+fn to_millis(elapsed: &Duration) -> u64 {
+    elapsed.min(&MAX_ELAPSED).as_millis() as u64
+}
+
+pub fn serialize<S: Serializer>(elapsed: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    to_millis(elapsed).serialize(serializer)
+}
+
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+    u64::deserialize(deserializer).map(Duration::from_millis)
+}
+
+pub mod option {
+    use super::to_millis;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::time::Duration;
+
+    pub fn serialize<S: Serializer>(
+        elapsed: &Option<Duration>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        elapsed.as_ref().map(to_millis).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Duration>, D::Error> {
+        Option::<u64>::deserialize(deserializer).map(|millis| millis.map(Duration::from_millis))
+    }
+}
+
+pub mod map {
+    use super::to_millis;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::{collections::HashMap, hash::Hash, time::Duration};
+
+    pub fn serialize<K, S>(map: &HashMap<K, Duration>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        K: Serialize + Eq + Hash,
+        S: Serializer,
+    {
+        let millis: HashMap<&K, u64> = map.iter().map(|(k, v)| (k, to_millis(v))).collect();
+        millis.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, K, D>(deserializer: D) -> Result<HashMap<K, Duration>, D::Error>
+    where
+        K: Deserialize<'de> + Eq + Hash,
+        D: Deserializer<'de>,
+    {
+        let millis = HashMap::<K, u64>::deserialize(deserializer)?;
+        Ok(millis
+            .into_iter()
+            .map(|(k, v)| (k, Duration::from_millis(v)))
+            .collect())
+    }
+}
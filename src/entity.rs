@@ -0,0 +1,376 @@
+//! Everything on the board and the store holding it.
+
+use crate::{
+    direction::Direction, grid::Sweep, object::Object, power::Effect, role::Role, sprite::Sprite,
+    util,
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashSet, slice};
+
+/// Names an entity for as long as it lives. Ids are never reused within a game.
+#[derive(Clone, Copy, Eq, Hash, Ord, PartialEq, PartialOrd, Debug, Serialize, Deserialize)]
+pub struct Id(u64);
+
+/// What an entity is, which decides the rules that apply to it.
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Serialize, Deserialize)]
+pub enum Kind {
+    /// The player's ship, which is always on the board.
+    Player,
+    /// The mirrored copy of the player from [`Effect::Xerox`].
+    Xerox,
+    /// A member of the formation.
+    Enemy,
+    /// The boss crossing the top of the board.
+    Boss,
+    /// Anything fired, by either side.
+    Bullet,
+    /// A power-up granting the effect when collected.
+    Power(Effect),
+    /// A power-up the game doesn't know, told apart by its number, such as one from a script.
+    Custom(u16),
+    /// A cell of the shield from [`Effect::Shield`].
+    Shield,
+    /// A cell of the column from [`Effect::Obstacle`].
+    Obstacle,
+    /// The block over the player from [`Effect::Follower`].
+    Follower,
+}
+
+/// The side an entity fights on.
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Serialize, Deserialize)]
+pub enum Team {
+    /// The player and everything fighting for them.
+    Player,
+    /// The invaders.
+    Enemy,
+    /// Power-ups and obstacles, which take no side.
+    Neutral,
+}
+
+/// Anything on the board that takes part in the game, made up of the same components
+/// whatever its kind.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Entity {
+    id: Id,
+    kind: Kind,
+    pos: (i32, i32),
+    // Where the entity started the tick, if it has moved since
+    #[serde(skip)]
+    trail: Option<(i32, i32)>,
+    sprite: Sprite,
+    color: Role,
+    team: Team,
+    health: i8,
+    velocity: Option<Direction>,
+    // Whoever fired a bullet
+    owner: Option<Id>,
+    is_explosive: bool,
+    is_numb: bool,
+}
+
+impl Object for Entity {
+    fn pos(&self) -> (i32, i32) {
+        self.pos
+    }
+    fn sprite(&self) -> Sprite {
+        self.sprite
+    }
+    fn color(&self) -> Role {
+        self.color
+    }
+}
+
+impl Entity {
+    /// A still entity with a single point of health. It only gets an id once spawned.
+    pub fn new(kind: Kind, pos: (i32, i32), sprite: Sprite, color: Role, team: Team) -> Self {
+        Self {
+            id: Id(0),
+            kind,
+            pos,
+            trail: None,
+            sprite,
+            color,
+            team,
+            health: 1,
+            velocity: None,
+            owner: None,
+            is_explosive: false,
+            is_numb: false,
+        }
+    }
+
+    /// Gives the entity that many hits before it dies.
+    pub fn with_health(self, health: i8) -> Self {
+        Self { health, ..self }
+    }
+
+    /// Sets the entity moving on its own.
+    pub fn with_velocity(self, velocity: Direction) -> Self {
+        Self {
+            velocity: Some(velocity),
+            ..self
+        }
+    }
+
+    /// Ties the entity to whoever fired it.
+    pub fn with_owner(self, owner: Id) -> Self {
+        Self {
+            owner: Some(owner),
+            ..self
+        }
+    }
+
+    /// Makes a bullet burst into shrapnel when it hits an enemy.
+    pub fn with_explosive(self, is_explosive: bool) -> Self {
+        Self {
+            is_explosive,
+            ..self
+        }
+    }
+
+    /// The id handed out when the entity was spawned.
+    pub fn id(&self) -> Id {
+        self.id
+    }
+
+    /// What the entity is.
+    pub fn kind(&self) -> Kind {
+        self.kind
+    }
+
+    /// The side the entity fights on.
+    pub fn team(&self) -> Team {
+        self.team
+    }
+
+    /// Switches sides, which is what Mindcontrol does to enemies.
+    pub fn set_team(&mut self, team: Team) {
+        self.team = team;
+    }
+
+    /// The effect a power-up grants when collected.
+    pub fn effect(&self) -> Option<Effect> {
+        match self.kind {
+            Kind::Power(effect) => Some(effect),
+            _ => None,
+        }
+    }
+
+    /// How many more hits the entity takes.
+    pub fn health(&self) -> i8 {
+        self.health
+    }
+
+    /// Takes one hit.
+    pub fn damage(&mut self) {
+        self.health -= 1;
+    }
+
+    /// Whether the entity has any health left.
+    pub fn is_alive(&self) -> bool {
+        self.health > 0
+    }
+
+    /// The direction the entity moves in on its own, one cell at a time.
+    pub fn velocity(&self) -> Option<Direction> {
+        self.velocity
+    }
+
+    /// Sets the entity moving in another direction.
+    pub fn set_velocity(&mut self, velocity: Direction) {
+        self.velocity = Some(velocity);
+    }
+
+    /// Whoever fired a bullet.
+    pub fn owner(&self) -> Option<Id> {
+        self.owner
+    }
+
+    /// Explosive bullets burst into shrapnel when they hit an enemy.
+    pub fn is_explosive(&self) -> bool {
+        self.is_explosive
+    }
+
+    /// Numb enemies stay where they are, and are drawn like a laser to show it.
+    pub fn is_numb(&self) -> bool {
+        self.is_numb
+    }
+
+    /// Stops an enemy in its tracks, as Numb does when it is shot.
+    pub fn set_numb(&mut self) {
+        self.is_numb = true;
+        self.color = Role::Laser;
+    }
+
+    /// Where a step in `dir` would take the entity, without checking the bounds.
+    pub fn new_pos(&self, dir: &Direction) -> (i32, i32) {
+        util::shift(&self.pos, dir)
+    }
+
+    /// Steps in `dir` unless that would leave any part of the sprite off a board of
+    /// `size` rows and columns.
+    pub fn shift(&mut self, dir: &Direction, size: (i32, i32)) {
+        let new_pos = self.new_pos(dir);
+        let (rows, cols) = self.extent();
+        let far_corner = (new_pos.0 + rows - 1, new_pos.1 + cols - 1);
+        if !util::out_of_bounds(new_pos, size) && !util::out_of_bounds(far_corner, size) {
+            self.step(new_pos);
+        }
+    }
+
+    /// Moves one cell along its velocity, even off the board.
+    pub fn advance(&mut self) {
+        if let Some(velocity) = self.velocity {
+            self.step(self.new_pos(&velocity));
+        }
+    }
+
+    fn step(&mut self, pos: (i32, i32)) {
+        self.trail.get_or_insert(self.pos);
+        self.pos = pos;
+    }
+
+    /// Puts the entity on another row without it passing over anything on the way.
+    pub fn set_row(&mut self, row: i32) {
+        self.pos.0 = row;
+        self.trail = None;
+    }
+
+    /// Puts the entity on another column without it passing over anything on the way.
+    pub fn set_col(&mut self, col: i32) {
+        self.pos.1 = col;
+        self.trail = None;
+    }
+
+    /// The path the entity took this tick.
+    pub fn sweep(&self) -> Sweep {
+        Sweep::new(self.trail.unwrap_or(self.pos), self.pos, self.extent())
+    }
+
+    /// Whether the two entities ran into each other this tick, even if they have
+    /// already passed each other by now.
+    pub fn crosses(&self, other: &Entity) -> bool {
+        self.sweep().crosses(&other.sweep())
+    }
+}
+
+/// Every entity in the game, oldest first.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct Entities {
+    next: u64,
+    entities: Vec<Entity>,
+}
+
+impl Entities {
+    /// Adds the entity to the board, handing out its id.
+    pub fn spawn(&mut self, mut entity: Entity) -> Id {
+        self.next += 1;
+        let id = Id(self.next);
+        entity.id = id;
+        self.entities.push(entity);
+        id
+    }
+
+    /// The entity with the given id, if it is still on the board.
+    pub fn get(&self, id: Id) -> Option<&Entity> {
+        let index = self.index(id)?;
+        Some(&self.entities[index])
+    }
+
+    /// The entity with the given id, if it is still on the board.
+    pub fn get_mut(&mut self, id: Id) -> Option<&mut Entity> {
+        let index = self.index(id)?;
+        Some(&mut self.entities[index])
+    }
+
+    // Entities are only ever pushed with a growing id, so they stay sorted by it
+    fn index(&self, id: Id) -> Option<usize> {
+        self.entities.binary_search_by_key(&id, Entity::id).ok()
+    }
+
+    /// Every entity, oldest first.
+    pub fn iter(&self) -> slice::Iter<'_, Entity> {
+        self.entities.iter()
+    }
+
+    /// Every entity, oldest first.
+    pub fn iter_mut(&mut self) -> slice::IterMut<'_, Entity> {
+        self.entities.iter_mut()
+    }
+
+    /// Every entity of the given kind, oldest first.
+    pub fn of(&self, kind: Kind) -> impl Iterator<Item = &Entity> {
+        self.iter().filter(move |entity| entity.kind == kind)
+    }
+
+    /// Every entity of the given kind, oldest first.
+    pub fn of_mut(&mut self, kind: Kind) -> impl Iterator<Item = &mut Entity> {
+        self.iter_mut().filter(move |entity| entity.kind == kind)
+    }
+
+    /// Bullets fired by either side.
+    pub fn bullets(&self, team: Team) -> impl Iterator<Item = &Entity> {
+        self.of(Kind::Bullet)
+            .filter(move |bullet| bullet.team == team)
+    }
+
+    /// The oldest entity of the given kind.
+    pub fn first(&self, kind: Kind) -> Option<&Entity> {
+        self.of(kind).next()
+    }
+
+    /// Keeps only the entities `keep` holds on to. Bullets go along with whoever
+    /// fired them.
+    pub fn retain<F: FnMut(&Entity) -> bool>(&mut self, mut keep: F) {
+        self.take(|entity| !keep(entity));
+    }
+
+    /// Despawns the entities `gone` picks, like [`Entities::retain`], and hands
+    /// them back. Bullets that went along with them aren't included.
+    pub fn take<F: FnMut(&Entity) -> bool>(&mut self, mut gone: F) -> Vec<Entity> {
+        let mut taken = vec![];
+        self.entities.retain(|entity| {
+            let is_gone = gone(entity);
+            if is_gone {
+                taken.push(entity.clone());
+            }
+            !is_gone
+        });
+        if !taken.is_empty() {
+            let ids: HashSet<Id> = taken.iter().map(Entity::id).collect();
+            self.entities
+                .retain(|entity| !entity.owner.is_some_and(|owner| ids.contains(&owner)));
+        }
+        taken
+    }
+
+    /// Forgets where entities came from, so that the next tick's paths start where
+    /// they are now.
+    pub fn settle(&mut self) {
+        for entity in self.entities.iter_mut() {
+            entity.trail = None;
+        }
+    }
+
+    /// Takes the entity off the board, along with its bullets.
+    pub fn despawn(&mut self, id: Id) {
+        self.retain(|entity| entity.id != id);
+    }
+
+    /// Takes the entities off the board, along with their bullets.
+    pub fn despawn_all(&mut self, ids: &HashSet<Id>) {
+        if !ids.is_empty() {
+            self.retain(|entity| !ids.contains(&entity.id));
+        }
+    }
+
+    /// How many entities are on the board.
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    /// Whether the board is empty, which it never is in a game, since the player is on it.
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+}
@@ -0,0 +1,108 @@
+//! Everything that can go wrong outside of the rules themselves.
+
+use crate::logic::{MIN_HEIGHT, MIN_WIDTH};
+use std::{fmt, io};
+
+/// What can keep a game from starting or carrying on, short of a bug.
+#[derive(Debug)]
+pub enum Error {
+    /// A board too small for the first wave to fit above the player.
+    Board {
+        /// The rows asked for.
+        rows: i32,
+        /// The columns asked for.
+        cols: i32,
+    },
+    /// A terminal too small for the board, as `(rows, cols)`.
+    Terminal {
+        /// The size of the terminal.
+        size: (i32, i32),
+        /// The size it takes at least.
+        needed: (i32, i32),
+    },
+    /// A theme that isn't built in and couldn't be read from a file either.
+    Theme {
+        /// The theme or file name given.
+        name: String,
+        /// Why it couldn't be loaded.
+        reason: String,
+    },
+    /// A port that isn't a number between 0 and 65535.
+    Port(String),
+    /// A connection to an opponent or spectators that couldn't be made or broke off.
+    Network {
+        /// What the connection was for.
+        context: String,
+        /// Why it failed.
+        source: io::Error,
+    },
+    /// Command-line options that don't make sense, or don't make sense together.
+    Usage(String),
+    /// A file given on the command line that couldn't be read or written, or that
+    /// didn't hold what it should.
+    File {
+        /// The file as given.
+        path: String,
+        /// What was wrong with it.
+        reason: String,
+    },
+    /// A script that doesn't compile, or that failed while running one of its hooks.
+    Script {
+        /// The script's file name.
+        name: String,
+        /// The error rhai reported.
+        reason: String,
+    },
+    /// Any other failure to read or write, from the terminal to the save file.
+    Io(io::Error),
+}
+
+/// Shorthand for results that fail with an [`Error`].
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    /// Wraps a failed connection with what it was for.
+    pub fn network(context: impl Into<String>) -> impl FnOnce(io::Error) -> Error {
+        let context = context.into();
+        move |source| Error::Network { context, source }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Board { rows, cols } => write!(
+                f,
+                "A board of {rows}x{cols} is too small, it takes at least {MIN_HEIGHT}x{MIN_WIDTH}"
+            ),
+            Error::Terminal { size, needed } => write!(
+                f,
+                "The terminal is {}x{} but the game takes at least {}x{}",
+                size.0, size.1, needed.0, needed.1
+            ),
+            Error::Theme { name, reason } => write!(f, "Couldn't load the theme {name}: {reason}"),
+            Error::Port(port) => write!(f, "Invalid port: {port}"),
+            Error::Network { context, source } => write!(f, "{context}: {source}"),
+            Error::Usage(usage) => write!(f, "{usage}"),
+            Error::File { path, reason } => write!(f, "{path}: {reason}"),
+            Error::Script { name, reason } => write!(f, "The script {name} failed: {reason}"),
+            Error::Io(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Network { source, .. } => Some(source),
+            Error::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
@@ -0,0 +1,121 @@
+//! What comes out of a tick.
+
+use crate::entity::Id;
+use crate::logic::{BOSS_SCORE, ENEMY_SCORE};
+use crate::power::Effect;
+use std::fmt;
+
+/// What took an enemy out.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Weapon {
+    /// The player's bullets. Ultra beams and grenade shrapnel count as bullets too.
+    Bullet,
+    /// Numb enemies the formation ran into.
+    Collision,
+    /// [`Effect::Explode`] going off nearby.
+    Explosion,
+    /// An obstacle the enemy ran into.
+    Obstacle,
+    /// Its own laser hitting the player under [`Effect::Vendetta`].
+    Vendetta,
+    /// The player ramming the formation under [`Effect::Kamizake`].
+    Kamizake,
+}
+
+/// Why the player lost a ship.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Cause {
+    /// An enemy laser.
+    Laser,
+    /// Kamizake into the formation, which takes the whole wave along.
+    Ram,
+    /// Kamizake off the edge of the board.
+    Crash,
+}
+
+/// Something noteworthy that happened during a tick, read back with [`Logic::events`].
+///
+/// [`Logic::events`]: crate::logic::Logic::events
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Event {
+    /// An enemy was taken out.
+    EnemyKilled {
+        /// The enemy, no longer on the board.
+        id: Id,
+        /// Where it was.
+        pos: (i32, i32),
+        /// What took it out.
+        by: Weapon,
+    },
+    /// The player lost a ship.
+    PlayerHit {
+        /// What it was lost to.
+        cause: Cause,
+    },
+    /// A shot fired by the player rather than held up by the reload.
+    PlayerShot,
+    /// The player collected a power-up.
+    PowerCollected(Effect),
+    /// The player collected a power-up of [`Kind::Custom`](crate::Kind::Custom), by its number.
+    CustomCollected(u16),
+    /// An effect ran out.
+    EffectExpired(Effect),
+    /// The boss appeared at the top of the board.
+    BossSpawned,
+    /// The boss got across the board alive.
+    BossEscaped,
+    /// The boss was shot down.
+    BossKilled,
+    /// The formation reached the player's row, which ends the game.
+    FormationLanded,
+    /// The last enemy of the wave on the given level was killed.
+    WaveCleared(i32),
+    /// Mind-controlled enemies that vanished once the effect ran out.
+    Released(usize),
+}
+
+impl Event {
+    /// Whether the event earns points, which is also what adds up to combos and garbage.
+    pub fn scores(&self) -> bool {
+        match self {
+            Event::EnemyKilled { by, .. } => matches!(by, Weapon::Bullet | Weapon::Collision),
+            Event::BossKilled => true,
+            _ => false,
+        }
+    }
+
+    /// The points the event is worth on the given level.
+    pub fn points(&self, level: i32) -> i32 {
+        match self {
+            Event::EnemyKilled { .. } if self.scores() => ENEMY_SCORE * level,
+            Event::BossKilled => BOSS_SCORE * level,
+            _ => 0,
+        }
+    }
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Event::EnemyKilled { .. } => write!(f, "Killed an enemy"),
+            Event::PlayerHit {
+                cause: Cause::Laser,
+            } => write!(f, "Lost a ship to a laser"),
+            Event::PlayerHit { cause: Cause::Ram } => write!(f, "Lost a ship ramming them"),
+            Event::PlayerHit {
+                cause: Cause::Crash,
+            } => write!(f, "Lost a ship off the edge"),
+            Event::PlayerShot => write!(f, "Fired a shot"),
+            Event::PowerCollected(effect) => write!(f, "Got {effect}"),
+            Event::CustomCollected(custom) => write!(f, "Got custom power-up {custom}"),
+            Event::EffectExpired(effect) => write!(f, "{effect} ran out"),
+            Event::BossSpawned => write!(f, "The boss showed up"),
+            Event::BossEscaped => write!(f, "The boss got away"),
+            Event::BossKilled => write!(f, "Shot down the boss"),
+            Event::FormationLanded => write!(f, "The invaders landed"),
+            Event::WaveCleared(level) => write!(f, "Cleared wave {level}"),
+            Event::Released(1) => write!(f, "Released 1 enemy"),
+            Event::Released(count) => write!(f, "Released {count} enemies"),
+        }
+    }
+}
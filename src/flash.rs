@@ -0,0 +1,100 @@
+use crate::{object::Object, role::Role, sprite::Sprite, util};
+
+// Updates each glyph of a flash stays on screen for
+const GLYPH_TICKS: usize = 2;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub(crate) enum Burst {
+    // Something got shot down
+    Explosion,
+    // The area caught by a grenade or Effect::Explode
+    Blast,
+    // What's left behind by Effect::Ultra
+    Beam,
+    // The player getting hit
+    Wreck,
+}
+
+impl Burst {
+    fn glyphs(&self) -> &'static [char] {
+        match self {
+            Burst::Explosion => &['*', '+', '.'],
+            Burst::Blast => &['#', '+', '.'],
+            Burst::Beam => &['|', ':', '.'],
+            Burst::Wreck => &['X', 'x', '+', '.'],
+        }
+    }
+
+    fn color(&self) -> Role {
+        match self {
+            Burst::Explosion | Burst::Blast => Role::Explosion,
+            Burst::Beam => Role::Ultra,
+            Burst::Wreck => Role::Player,
+        }
+    }
+}
+
+/// A single cell of an animation, which is only ever drawn and never collides.
+///
+/// Flashes are read back with [`Logic::flashes`](crate::Logic::flashes) and run their
+/// course on their own.
+#[derive(Clone)]
+pub struct Flash {
+    pos: (i32, i32),
+    burst: Burst,
+    age: usize,
+}
+
+impl Object for Flash {
+    fn pos(&self) -> (i32, i32) {
+        self.pos
+    }
+    fn sprite(&self) -> Sprite {
+        let glyphs = self.burst.glyphs();
+        Sprite::Glyph(glyphs[(self.age / GLYPH_TICKS).min(glyphs.len() - 1)])
+    }
+    fn color(&self) -> Role {
+        self.burst.color()
+    }
+}
+
+impl Flash {
+    pub(crate) fn new(pos: (i32, i32), burst: Burst) -> Self {
+        Self { pos, burst, age: 0 }
+    }
+
+    // Every cell of a rectangle that falls within the board
+    fn cells(pos: (i32, i32), extent: (i32, i32), size: (i32, i32), burst: Burst) -> Vec<Flash> {
+        let mut cells = vec![];
+        for row in pos.0..pos.0 + extent.0 {
+            for col in pos.1..pos.1 + extent.1 {
+                if !util::out_of_bounds((row, col), size) {
+                    cells.push(Flash::new((row, col), burst));
+                }
+            }
+        }
+        cells
+    }
+
+    pub(crate) fn cover<T: Object>(object: &T, size: (i32, i32), burst: Burst) -> Vec<Flash> {
+        Flash::cells(object.pos(), object.extent(), size, burst)
+    }
+
+    pub(crate) fn area(
+        center: &(i32, i32),
+        radius: i32,
+        size: (i32, i32),
+        burst: Burst,
+    ) -> Vec<Flash> {
+        let pos = (center.0 - radius, center.1 - radius);
+        Flash::cells(pos, (2 * radius + 1, 2 * radius + 1), size, burst)
+    }
+
+    pub(crate) fn age(&mut self) {
+        self.age += 1;
+    }
+
+    pub(crate) fn is_over(&self) -> bool {
+        self.age >= self.burst.glyphs().len() * GLYPH_TICKS
+    }
+}
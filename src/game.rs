@@ -1,121 +1,238 @@
 use crate::{
-    direction::Direction,
-    logic::{Logic, COLOR_OBSTACLES, COLOR_POWERS, COLOR_SHIELDS},
+    achievements::Achievements,
+    controller::{Action, PlayerController},
+    journal::Journal,
+    keymap::Keymap,
     printer::Printer,
+    render::{Renderer, Scene, Terminal},
+    replay::Replay,
+    save,
+    session::{Session, REFRESH_RATE},
+    spectate::Broadcast,
+    versus::{Status, Versus},
+};
+use ncurses::{keypad, leaveok, WINDOW};
+use space_invaders::event::Event;
+use std::{
+    thread,
+    time::{Duration, Instant},
 };
-use ncurses::{box_, keypad, leaveok, wgetch, KEY_LEFT, KEY_RIGHT, WINDOW};
-use std::time::{Duration, Instant};
 
-const MAX_PLAYER_LIVES: i8 = 3;
-const REFRESH_RATE: Duration = Duration::from_millis(50);
+const FRAME_RATE: Duration = Duration::from_millis(33);
+// After a stall, only this many updates are caught up on before moving on
+const MAX_CATCH_UP: usize = 5;
+// Sprites switch frames every this many updates
+const ANIMATION_RATE: usize = 10;
 
 pub struct Invaders {
-    lives: i8,
-    level: i32,
-    input: i32,
-    score: i32,
-    gate: Logic,
-    last_update: Instant,
+    session: Session,
     window: WINDOW,
+    renderer: Box<dyn Renderer>,
+    achievements: Achievements,
+    journal: Journal,
+    paused: bool,
+    versus: Option<Versus>,
+    broadcast: Option<Broadcast>,
+    controller: Box<dyn PlayerController>,
+    // Only to show the keys on the help screen, the controller reads them on its own
+    keymap: Keymap,
+    recording: Option<Replay>,
 }
 
 impl Invaders {
-    pub fn new(win: WINDOW) -> Self {
+    pub fn new(
+        win: WINDOW,
+        session: Session,
+        controller: Box<dyn PlayerController>,
+        keymap: Keymap,
+    ) -> Self {
         Self {
-            lives: MAX_PLAYER_LIVES,
-            level: 0,
-            input: 0,
-            score: 0,
-            last_update: Instant::now(),
+            session,
             window: win,
-            gate: Logic::new(win),
+            renderer: Box::new(Terminal::new(win)),
+            achievements: Achievements::load(),
+            journal: Journal::default(),
+            paused: false,
+            versus: None,
+            broadcast: None,
+            controller,
+            keymap,
+            recording: None,
         }
     }
 
+    pub fn set_versus(&mut self, versus: Versus) {
+        self.versus = Some(versus);
+    }
+
+    pub fn set_broadcast(&mut self, broadcast: Broadcast) {
+        self.broadcast = Some(broadcast);
+    }
+
+    pub fn set_recording(&mut self, replay: Replay) {
+        self.recording = Some(replay);
+    }
+
+    pub fn take_recording(&mut self) -> Option<Replay> {
+        self.recording.take()
+    }
+
     pub fn init(&mut self) {
-        box_(self.window, 0, 0);
         leaveok(self.window, true);
         keypad(self.window, true);
     }
 
-    fn read_input(&mut self) {
-        self.input = wgetch(self.window);
-    }
-
     fn update(&mut self) {
-        if self.gate.enemies().is_empty() {
-            self.gate.level_up(&mut self.level);
-            if self.lives < MAX_PLAYER_LIVES {
-                self.lives += 1;
+        self.session.next_wave();
+        self.achievements.advance(REFRESH_RATE);
+
+        let actions = self.controller.act(&self.session.gate);
+        if let Some(recording) = &mut self.recording {
+            recording.record(&actions);
+        }
+        for action in actions {
+            match action {
+                Action::Pause => self.paused = true,
+                action => self.session.play(action),
             }
         }
 
-        if self.input == ' ' as i32 {
-            self.gate.player_shoot();
-        } else if self.input == KEY_RIGHT {
-            self.gate.player_move(&Direction::Right);
-        } else if self.input == KEY_LEFT {
-            self.gate.player_move(&Direction::Left);
-        } else if self.input == 'q' as i32 {
-            self.lives = -1;
+        if let Err(e) = self.session.tick() {
+            self.journal.note(e.to_string());
         }
-
-        if self.last_update.elapsed() >= REFRESH_RATE {
-            self.gate.generate();
-            if self.gate.shift(&self.level) {
-                self.lives = -1;
-            }
-            if self.gate.handle() {
-                self.lives -= 1;
-            }
-            if self.gate.hit(&self.level) {
-                self.lives -= 1;
+        for feat in self.session.gate.feats() {
+            self.achievements.unlock(*feat);
+        }
+        for event in self.session.gate.events() {
+            match event {
+                // Named after the script behind it, which the event doesn't know
+                Event::CustomCollected(custom) => match self.session.scripts.name(*custom) {
+                    Some(name) => self.journal.note(format!("Got {name}")),
+                    None => self.journal.record(event),
+                },
+                event => self.journal.record(event),
             }
-            self.score += self.gate.score_increment();
-            self.gate.score_reset();
-            self.last_update = Instant::now();
         }
+        if let Some(versus) = &mut self.versus {
+            let status = Status {
+                score: self.session.score,
+                lives: self.session.lives,
+                level: self.session.level,
+            };
+            versus.sync(&mut self.session.gate, status);
+        }
+        self.session.end_tick();
     }
 
-    fn print(&self) {
-        Printer::clear(self.window);
-        Printer::header(self.score, self.window, self.lives);
-        let enemies = self.gate.enemies();
-        Printer::shooters(self.window, enemies);
-        let player = self.gate.player();
-        Printer::shooter(self.window, player);
-        let powers = self.gate.powers();
-        Printer::objects(self.window, powers, COLOR_POWERS);
-        let shields = self.gate.shields();
-        Printer::objects(self.window, shields, COLOR_SHIELDS);
-        let obstacles = self.gate.obstacles();
-        Printer::objects(self.window, obstacles, COLOR_OBSTACLES);
-        if let Some(xerox) = self.gate.xerox() {
-            Printer::shooter(self.window, xerox);
+    fn print(&mut self) {
+        let scene = Scene {
+            score: self.session.score,
+            lives: self.session.lives,
+            level: self.session.level,
+            frame: self.session.ticks / ANIMATION_RATE,
+            logic: &self.session.gate,
+            journal: &self.journal,
+            rival: self.versus.as_ref().map(rival),
+            toast: self
+                .achievements
+                .toast()
+                .map(|achievement| format!("Achievement: {achievement}")),
+        };
+        self.renderer.render(&scene);
+        if let Some(broadcast) = &mut self.broadcast {
+            broadcast.publish(&scene);
         }
-        if let Some(follower) = self.gate.follower() {
-            Printer::object(self.window, follower);
+    }
+
+    fn pause(&mut self) {
+        self.paused = false;
+        // The rival's game wouldn't wait for us
+        if self.versus.is_some() {
+            return;
         }
-        if let Some(boss) = self.gate.boss() {
-            Printer::object(self.window, boss);
+        loop {
+            match Printer::menu("PAUSED", &["Resume", "Help", "Quit"]) {
+                0 => break,
+                1 => Printer::help(&self.keymap),
+                _ => {
+                    self.session.play(Action::Quit);
+                    break;
+                }
+            }
         }
-        Printer::footer(self.gate.active_effects());
+        self.renderer.invalidate();
     }
 
     fn is_game_over(&self) -> bool {
-        self.lives <= -1
+        self.session.is_over()
+            || self.session.has_quit()
+            || self
+                .versus
+                .as_ref()
+                .is_some_and(|versus| versus.has_opponent_lost())
     }
 
-    fn quit(&self) {
-        Printer::quit(self.score);
+    fn quit(&mut self) {
+        if let Some(versus) = &mut self.versus {
+            if versus.has_opponent_lost() {
+                Printer::victory(self.session.score);
+            } else {
+                versus.lose();
+                Printer::quit(self.session.score);
+            }
+            return;
+        }
+        if self.session.has_quit()
+            && Printer::confirm("Save this run?")
+            && save::store(&self.session.snapshot()).is_ok()
+        {
+            return;
+        }
+        Printer::quit(self.session.score);
     }
 
     pub fn game_loop(&mut self) {
+        let mut next_update = Instant::now();
+        let mut next_frame = Instant::now();
         while !self.is_game_over() {
-            self.update();
-            self.print();
-            self.read_input();
+            let now = Instant::now();
+            let mut updates = 0;
+            while next_update <= now && updates < MAX_CATCH_UP && !self.is_game_over() {
+                self.update();
+                next_update += REFRESH_RATE;
+                updates += 1;
+            }
+            if next_update <= now {
+                next_update = now + REFRESH_RATE;
+            }
+            if self.paused {
+                self.pause();
+                next_update = Instant::now();
+                next_frame = Instant::now();
+                continue;
+            }
+            if next_frame <= now {
+                self.print();
+                next_frame = now + FRAME_RATE;
+            }
+            let wake = next_update.min(next_frame);
+            thread::sleep(wake.saturating_duration_since(Instant::now()));
         }
         self.quit();
     }
 }
+
+fn rival(versus: &Versus) -> String {
+    if !versus.is_connected() {
+        return "RIVAL LEFT".to_string();
+    }
+    match versus.opponent() {
+        Some(status) => format!(
+            "RIVAL {} L{} {}",
+            status.score,
+            status.level,
+            "*".repeat(status.lives.max(0) as usize)
+        ),
+        None => "RIVAL ...".to_string(),
+    }
+}
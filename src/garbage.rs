@@ -0,0 +1,29 @@
+//! What versus opponents send each other.
+
+use rand::{
+    distributions::{Distribution, Standard},
+    Rng,
+};
+use serde::{Deserialize, Serialize};
+
+/// A penalty a versus opponent inflicts on the player by killing enemies, handed to
+/// [`Logic::receive`](crate::Logic::receive).
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub enum Garbage {
+    /// An extra row of enemies on top of the formation.
+    Enemies,
+    /// A forced [`Effect::Jump`](crate::Effect::Jump).
+    Jump,
+    /// The player's attack cooldown is doubled for a while.
+    Slowdown,
+}
+
+impl Distribution<Garbage> for Standard {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Garbage {
+        match rng.gen_range(0..=2) {
+            0 => Garbage::Enemies,
+            1 => Garbage::Jump,
+            _ => Garbage::Slowdown,
+        }
+    }
+}
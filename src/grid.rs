@@ -0,0 +1,221 @@
+//! Finding out what ran into what during a tick.
+
+use crate::entity::{Entity, Id};
+use std::collections::HashMap;
+
+/// The room an entity took up over a tick, sliding in a straight line from where it
+/// started to where it ended up.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Sweep {
+    from: (i32, i32),
+    to: (i32, i32),
+    extent: (i32, i32),
+}
+
+impl Sweep {
+    /// The room an entity of `extent` takes up going from `from` to `to`.
+    pub fn new(from: (i32, i32), to: (i32, i32), extent: (i32, i32)) -> Self {
+        Self { from, to, extent }
+    }
+
+    /// Whether the two ever shared a cell during the tick, including passing through
+    /// each other on the way to where they ended up.
+    pub fn crosses(&self, other: &Sweep) -> bool {
+        let rows = overlap(
+            (self.from.0, self.to.0, self.extent.0),
+            (other.from.0, other.to.0, other.extent.0),
+        );
+        let cols = overlap(
+            (self.from.1, self.to.1, self.extent.1),
+            (other.from.1, other.to.1, other.extent.1),
+        );
+        match (rows, cols) {
+            (Some((enter_rows, exit_rows)), Some((enter_cols, exit_cols))) => {
+                let (enter, exit) = (enter_rows.max(enter_cols), exit_rows.min(exit_cols));
+                enter < exit && enter < 1.0 && exit > 0.0
+            }
+            _ => false,
+        }
+    }
+
+    // Every cell the sweep passes over, and then some when moving diagonally
+    fn cells(&self) -> impl Iterator<Item = (i32, i32)> {
+        let top = self.from.0.min(self.to.0);
+        let left = self.from.1.min(self.to.1);
+        let bottom = self.from.0.max(self.to.0) + self.extent.0;
+        let right = self.from.1.max(self.to.1) + self.extent.1;
+        (top..bottom).flat_map(move |row| (left..right).map(move |col| (row, col)))
+    }
+}
+
+// When, between 0 at the start of the tick and 1 at its end, two spans sliding along one
+// axis overlap. Each span is where it starts, where it ends and how long it is
+fn overlap(this: (i32, i32, i32), other: (i32, i32, i32)) -> Option<(f64, f64)> {
+    let start = f64::from(this.0 - other.0);
+    let speed = f64::from((this.1 - this.0) - (other.1 - other.0));
+    // The spans overlap for as long as `start + speed * t` lies strictly between these
+    let (low, high) = (-f64::from(this.2), f64::from(other.2));
+    if speed == 0.0 {
+        (low < start && start < high).then_some((f64::NEG_INFINITY, f64::INFINITY))
+    } else {
+        let (a, b) = ((low - start) / speed, (high - start) / speed);
+        Some((a.min(b), a.max(b)))
+    }
+}
+
+/// Which entities passed over each cell during the tick, so that finding what an entity
+/// ran into only looks at the cells it passed over instead of at everything else on
+/// the board.
+///
+/// A grid is a snapshot: it has to be built again once entities move or go away.
+#[derive(Default)]
+pub struct Grid {
+    cells: HashMap<(i32, i32), Vec<(Id, Sweep)>>,
+}
+
+impl Grid {
+    /// A grid of where the entities went this tick.
+    pub fn new<'a>(entities: impl IntoIterator<Item = &'a Entity>) -> Self {
+        let mut grid = Grid::default();
+        for entity in entities {
+            let sweep = entity.sweep();
+            for cell in sweep.cells() {
+                grid.cells
+                    .entry(cell)
+                    .or_default()
+                    .push((entity.id(), sweep));
+            }
+        }
+        grid
+    }
+
+    /// Every entity in the grid whose path crossed that of `entity`, other than itself and
+    /// whoever fired it, each listed once.
+    pub fn crossing(&self, entity: &Entity) -> Vec<Id> {
+        let sweep = entity.sweep();
+        let mut ids = vec![];
+        for cell in sweep.cells() {
+            for (id, other) in self.at(cell) {
+                if !Grid::skips(entity, *id) && !ids.contains(id) && sweep.crosses(other) {
+                    ids.push(*id);
+                }
+            }
+        }
+        ids
+    }
+
+    /// Whether any entity in the grid, other than `entity` itself and whoever fired it,
+    /// crossed its path.
+    pub fn crosses(&self, entity: &Entity) -> bool {
+        let sweep = entity.sweep();
+        sweep.cells().any(|cell| {
+            self.at(cell)
+                .iter()
+                .any(|(id, other)| !Grid::skips(entity, *id) && sweep.crosses(other))
+        })
+    }
+
+    // An entity never runs into itself, nor a bullet into its shooter
+    fn skips(entity: &Entity, id: Id) -> bool {
+        id == entity.id() || entity.owner() == Some(id)
+    }
+
+    fn at(&self, pos: (i32, i32)) -> &[(Id, Sweep)] {
+        self.cells.get(&pos).map_or(&[], Vec::as_slice)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Grid, Sweep};
+    use crate::{
+        direction::Direction,
+        entity::{Entities, Entity, Kind, Team},
+        role::Role,
+        sprite::Sprite,
+    };
+
+    const CELL: (i32, i32) = (1, 1);
+
+    fn moving(from: (i32, i32), to: (i32, i32)) -> Sweep {
+        Sweep::new(from, to, CELL)
+    }
+
+    fn still(pos: (i32, i32)) -> Sweep {
+        moving(pos, pos)
+    }
+
+    #[test]
+    fn head_on_swap() {
+        let up = moving((5, 5), (4, 5));
+        let down = moving((4, 5), (5, 5));
+        assert!(up.crosses(&down));
+        assert!(down.crosses(&up));
+    }
+
+    #[test]
+    fn ending_on_the_same_cell() {
+        let right = moving((5, 4), (5, 5));
+        let left = moving((5, 6), (5, 5));
+        assert!(right.crosses(&left));
+    }
+
+    #[test]
+    fn diagonal_paths() {
+        let right_up = moving((10, 10), (9, 11));
+        assert!(right_up.crosses(&still((9, 11))));
+        // Crossing each other halfway
+        assert!(right_up.crosses(&moving((9, 10), (10, 11))));
+        // Side by side, a column apart
+        assert!(!right_up.crosses(&moving((10, 12), (9, 13))));
+    }
+
+    #[test]
+    fn still_target() {
+        let target = still((3, 3));
+        assert!(moving((4, 3), (3, 3)).crosses(&target));
+        assert!(!moving((4, 4), (3, 4)).crosses(&target));
+        assert!(target.crosses(&target));
+    }
+
+    #[test]
+    fn wide_sprites() {
+        let boss = Sweep::new((2, 10), (2, 11), (1, 3));
+        assert!(moving((3, 13), (2, 13)).crosses(&boss));
+        // Standing still, it covers every one of its columns
+        assert!(moving((3, 13), (2, 13)).crosses(&Sweep::new((2, 11), (2, 11), (1, 3))));
+        assert!(!moving((3, 14), (2, 14)).crosses(&boss));
+    }
+
+    #[test]
+    fn a_cell_apart_is_a_miss() {
+        assert!(!still((5, 5)).crosses(&still((5, 7))));
+        assert!(!still((5, 5)).crosses(&still((5, 6))));
+        // Ending up next to each other without ever sharing a cell
+        assert!(!moving((5, 4), (5, 5)).crosses(&moving((5, 7), (5, 6))));
+        assert!(!moving((4, 5), (3, 5)).crosses(&moving((6, 5), (5, 5))));
+    }
+
+    #[test]
+    fn bullets_pass_their_shooter() {
+        let mut entities = Entities::default();
+        let enemy = |pos| Entity::new(Kind::Enemy, pos, Sprite::Enemy, Role::Enemy, Team::Player);
+        let shooter = entities.spawn(enemy((5, 5)));
+        let bystander = entities.spawn(enemy((4, 6)));
+        let bullet = Entity::new(
+            Kind::Bullet,
+            (5, 5),
+            Sprite::Bullet,
+            Role::Bullet,
+            Team::Player,
+        )
+        .with_velocity(Direction::RightUp)
+        .with_owner(shooter);
+        let bullet = entities.spawn(bullet);
+        entities.get_mut(bullet).unwrap().advance();
+
+        let grid = Grid::new(entities.of(Kind::Enemy));
+        let bullet = entities.get(bullet).unwrap();
+        assert_eq!(grid.crossing(bullet), vec![bystander]);
+    }
+}
@@ -0,0 +1,66 @@
+use crate::{
+    cli::Options,
+    controller::{Bot, PlayerController},
+    replay::{Playback, Replay, Setup},
+};
+use space_invaders::{event::Event, Error};
+
+// Plays a run without a terminal, with the autopilot or a recording at the controls, as
+// fast as it goes, then prints how it ended. Recordings stop when their moves run out, and
+// a failing script stops the run
+pub fn run(options: &Options) -> Result<(), Error> {
+    let replay = options.replay.as_deref().map(Replay::load).transpose()?;
+    let setup = replay
+        .as_ref()
+        .map_or_else(|| Setup::new(options), |replay| replay.setup.clone());
+    let limit = options
+        .ticks
+        .or(replay.as_ref().map(|replay| replay.ticks.len()));
+    let mut controller: Box<dyn PlayerController> = match replay {
+        Some(replay) => Box::new(Playback::new(None, replay)),
+        None => Box::new(Bot),
+    };
+    let mut recording = options.record.as_ref().map(|_| Replay::new(setup.clone()));
+
+    let mut session = setup.start(setup.scripts()?)?;
+    let mut kills = 0;
+    while !session.is_over()
+        && !session.has_quit()
+        && limit.is_none_or(|limit| session.ticks < limit)
+    {
+        session.next_wave();
+        let actions = controller.act(&session.gate);
+        if let Some(recording) = &mut recording {
+            recording.record(&actions);
+        }
+        for action in actions {
+            session.play(action);
+        }
+        session.tick()?;
+        kills += session
+            .gate
+            .events()
+            .iter()
+            .filter(|event| matches!(event, Event::EnemyKilled { .. }))
+            .count();
+        session.end_tick();
+    }
+
+    if let (Some(path), Some(recording)) = (&options.record, &recording) {
+        recording.store(path)?;
+    }
+    let outcome = if session.is_over() {
+        "game over"
+    } else {
+        "still playing"
+    };
+    println!("seed       {}", setup.seed);
+    println!("difficulty {}", setup.difficulty);
+    println!("ticks      {}", session.ticks);
+    println!("level      {}", session.level);
+    println!("score      {}", session.score);
+    println!("lives      {}", session.lives.max(0));
+    println!("kills      {kills}");
+    println!("outcome    {outcome}");
+    Ok(())
+}
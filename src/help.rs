@@ -0,0 +1,79 @@
+use crate::{controller::Action, keymap::Keymap};
+use space_invaders::{
+    logic::{POWER_COOLDOWN, YIELDS},
+    power::Effect,
+};
+
+const CONTROLS: [(&[Action], &str); 4] = [
+    (&[Action::Left, Action::Right], "Move"),
+    (&[Action::Shoot], "Shoot"),
+    (&[Action::Pause], "Pause"),
+    (&[Action::Quit], "Quit, offering to save the run"),
+];
+
+fn duration(effect: &Effect) -> String {
+    let seconds = POWER_COOLDOWN.as_secs();
+    match effect {
+        Effect::Clear | Effect::Explode | Effect::Ultra => "instant".to_string(),
+        Effect::Yield => format!("{YIELDS} moves"),
+        Effect::Follower => format!("{seconds}s or 1 hit"),
+        _ => format!("{seconds}s"),
+    }
+}
+
+// Breaks text into lines no wider than width, without splitting words
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = vec![];
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        if !line.is_empty() && line.len() + 1 + word.len() > width {
+            lines.push(line);
+            line = String::new();
+        }
+        if !line.is_empty() {
+            line += " ";
+        }
+        line += word;
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+// Every key bound to the actions, or a dash if a key map left them without any
+fn keys(keymap: &Keymap, actions: &[Action]) -> String {
+    let keys: Vec<String> = actions
+        .iter()
+        .flat_map(|action| keymap.keys(*action))
+        .collect();
+    if keys.is_empty() {
+        "-".to_string()
+    } else {
+        keys.join("/")
+    }
+}
+
+// The controls as bound in the key map and every power-up, laid out to fit the given width
+pub fn lines(width: usize, keymap: &Keymap) -> Vec<String> {
+    let mut lines = vec!["CONTROLS".to_string()];
+    for (actions, what) in CONTROLS {
+        lines.push(format!("  {:<11} {what}", keys(keymap, actions)));
+    }
+    lines.push(format!("  {:<11} Scroll this screen", "up/down"));
+    lines.push(String::new());
+    lines.push("POWER-UPS".to_string());
+    for effect in Effect::ALL {
+        lines.push(String::new());
+        lines.push(format!(
+            "  {} {:<11} {}",
+            effect.initial(),
+            effect.to_string(),
+            duration(&effect)
+        ));
+        for line in wrap(effect.description(), width.saturating_sub(4)) {
+            lines.push(format!("    {line}"));
+        }
+    }
+    lines
+}
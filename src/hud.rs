@@ -0,0 +1,101 @@
+use crate::{
+    canvas::{Canvas, Pen, Screen},
+    render::Scene,
+    window,
+};
+use ncurses::*;
+use space_invaders::{logic::POWER_COOLDOWN, role::Role};
+use std::time::Duration;
+
+// Borders, the status row and the effects beneath it
+pub const HUD_ROWS: i32 = 8;
+const BAR_SIZE: usize = 20;
+const BOSS_BAR_SIZE: usize = 10;
+// Effects this close to running out are drawn as a warning
+const WARNING: Duration = Duration::from_secs(3);
+
+pub struct Hud {
+    window: Option<WINDOW>,
+    screen: Option<Screen>,
+}
+
+impl Hud {
+    // Sits right below the game window, shrinking to whatever room is left
+    pub fn new(game: WINDOW) -> Self {
+        let y = getbegy(game) + getmaxy(game);
+        let rows = HUD_ROWS.min(getmaxy(stdscr()) - y);
+        let window = if rows >= 3 {
+            Some(window::get_mid_window(rows, getmaxx(game), y))
+        } else {
+            None
+        };
+        Self {
+            window,
+            screen: window.map(Screen::new),
+        }
+    }
+
+    pub fn draw(&mut self, scene: &Scene) {
+        let Some(screen) = &mut self.screen else {
+            return;
+        };
+        let mut canvas = screen.canvas();
+        Hud::paint(&mut canvas, scene);
+        screen.present(canvas);
+    }
+
+    pub fn touch(&self) {
+        if let Some(screen) = &self.screen {
+            screen.touch();
+        }
+    }
+
+    pub fn paint(canvas: &mut Canvas, scene: &Scene) {
+        let logic = scene.logic;
+        canvas.border();
+        let hud = Pen::new(Role::Hud);
+        canvas.write((1, 1), &format!("LEVEL {}", scene.level), hud);
+        if logic.combo() > 1 {
+            canvas.write((1, 10), &format!("COMBO x{}", logic.combo()), hud);
+        }
+        if let Some(escape) = logic.boss_escape() {
+            let boss = format!("BOSS {}", bar(escape, BOSS_BAR_SIZE));
+            let col = canvas.cols() - 1 - boss.chars().count() as i32;
+            canvas.write((1, col), &boss, hud);
+        }
+
+        let rows = canvas.rows() - 3;
+        let timers = logic.effect_timers();
+        for (row, (effect, remaining)) in timers.iter().enumerate() {
+            let row = row as i32;
+            if row == rows - 1 && timers.len() as i32 > rows {
+                let more = format!("+{} more", timers.len() as i32 - row);
+                canvas.write((row + 2, 1), &more, hud);
+                break;
+            }
+            let fill = remaining.as_secs_f32() / POWER_COOLDOWN.as_secs_f32();
+            let seconds = remaining.as_secs_f32().ceil() as u64;
+            let pen = if *remaining < WARNING {
+                Pen::new(Role::Laser).with(A_BOLD())
+            } else {
+                hud
+            };
+            let name = effect.to_string();
+            let timer = format!("{name:<11} {} {seconds:>2}s", bar(fill, BAR_SIZE));
+            canvas.write((row + 2, 1), &timer, pen);
+        }
+    }
+}
+
+impl Drop for Hud {
+    fn drop(&mut self) {
+        if let Some(window) = self.window {
+            delwin(window);
+        }
+    }
+}
+
+fn bar(fill: f32, size: usize) -> String {
+    let filled = ((fill * size as f32).ceil() as usize).min(size);
+    "█".repeat(filled) + "░".repeat(size - filled).as_str()
+}
@@ -0,0 +1,105 @@
+use crate::canvas::{Canvas, Pen, Screen};
+use ncurses::*;
+use space_invaders::{event::Event, role::Role};
+use std::collections::VecDeque;
+
+// Entries kept around, which is more than the panel ever shows
+const JOURNAL_SIZE: usize = 64;
+const PANEL_COLS: i32 = 28;
+// Narrower than this and the entries would be unreadable
+const MIN_PANEL_COLS: i32 = 14;
+
+// A running log of what happened in the game, newest last
+#[derive(Default)]
+pub struct Journal {
+    entries: VecDeque<String>,
+}
+
+impl Journal {
+    pub fn record(&mut self, event: &Event) {
+        // Every single kill or shot would drown out everything else
+        if let Event::EnemyKilled { .. } | Event::PlayerShot = event {
+            return;
+        }
+        self.note(event.to_string());
+    }
+
+    // Anything worth telling that isn't an event
+    pub fn note(&mut self, entry: String) {
+        if self.entries.len() == JOURNAL_SIZE {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(entry);
+    }
+
+    pub fn entries(&self) -> &VecDeque<String> {
+        &self.entries
+    }
+}
+
+// Shows the latest journal entries to the right of the game window, if there's room
+pub struct Panel {
+    window: Option<WINDOW>,
+    screen: Option<Screen>,
+}
+
+impl Panel {
+    pub fn new(game: WINDOW) -> Self {
+        let x = getbegx(game) + getmaxx(game);
+        let cols = PANEL_COLS.min(getmaxx(stdscr()) - x);
+        let window = if cols >= MIN_PANEL_COLS {
+            Some(newwin(getmaxy(game), cols, getbegy(game), x))
+        } else {
+            None
+        };
+        Self {
+            window,
+            screen: window.map(Screen::new),
+        }
+    }
+
+    pub fn draw(&mut self, journal: &Journal) {
+        let Some(screen) = &mut self.screen else {
+            return;
+        };
+        let mut canvas = screen.canvas();
+        Panel::paint(&mut canvas, journal);
+        screen.present(canvas);
+    }
+
+    pub fn touch(&self) {
+        if let Some(screen) = &self.screen {
+            screen.touch();
+        }
+    }
+
+    fn paint(canvas: &mut Canvas, journal: &Journal) {
+        canvas.border();
+        canvas.write((0, 2), " LOG ", Pen::new(Role::Hud));
+        let rows = (canvas.rows() - 2).max(0) as usize;
+        let width = (canvas.cols() - 2).max(0) as usize;
+        let entries = journal.entries();
+        let newest = entries.len().saturating_sub(1);
+        let shown = entries
+            .iter()
+            .enumerate()
+            .skip(entries.len().saturating_sub(rows));
+        for (row, (index, entry)) in shown.enumerate() {
+            let entry: String = entry.chars().take(width).collect();
+            let pen = if index == newest {
+                Pen::new(Role::Hud).with(A_BOLD())
+            } else {
+                Pen::new(Role::Hud)
+            };
+            canvas.write((row as i32 + 1, 1), &entry, pen);
+        }
+    }
+}
+
+impl Drop for Panel {
+    fn drop(&mut self) {
+        if let Some(window) = self.window {
+            delwin(window);
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use crate::controller::Action;
+use ncurses::{KEY_DOWN, KEY_LEFT, KEY_RIGHT, KEY_UP};
+use space_invaders::Error;
+use std::{collections::HashMap, fs};
+
+const DEFAULT_KEYS: [(&str, Action); 5] = [
+    ("space", Action::Shoot),
+    ("left", Action::Left),
+    ("right", Action::Right),
+    ("p", Action::Pause),
+    ("q", Action::Quit),
+];
+// Keys without a character of their own to write them down with
+const NAMED_KEYS: [(&str, i32); 7] = [
+    ("space", ' ' as i32),
+    ("tab", '\t' as i32),
+    ("enter", '\n' as i32),
+    ("left", KEY_LEFT),
+    ("right", KEY_RIGHT),
+    ("up", KEY_UP),
+    ("down", KEY_DOWN),
+];
+
+// Which key does what, read from lines of an action and a key such as `shoot z`. Actions
+// the file leaves out keep their usual keys, the others only answer to the keys given
+#[derive(Clone)]
+pub struct Keymap {
+    keys: HashMap<i32, Action>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let keys = DEFAULT_KEYS
+            .into_iter()
+            .filter_map(|(name, action)| Some((key(name)?, action)))
+            .collect();
+        Self { keys }
+    }
+}
+
+impl Keymap {
+    pub fn load(path: &str) -> Result<Keymap, Error> {
+        let invalid = |reason: String| Error::File {
+            path: path.to_string(),
+            reason,
+        };
+        let contents = fs::read_to_string(path).map_err(|e| invalid(e.to_string()))?;
+        let mut keymap = Keymap::default();
+        let mut bound = vec![];
+        for (number, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let at = |reason: &str| invalid(format!("line {}: {reason}", number + 1));
+            let Some((action, name)) = line.split_once(char::is_whitespace) else {
+                return Err(at("expected an action and a key"));
+            };
+            let action = Action::parse(action).ok_or_else(|| at("unknown action"))?;
+            let key = key(name.trim()).ok_or_else(|| at("unknown key"))?;
+            if !bound.contains(&action) {
+                keymap.keys.retain(|_, bound| *bound != action);
+                bound.push(action);
+            }
+            keymap.keys.insert(key, action);
+        }
+        Ok(keymap)
+    }
+
+    pub fn action(&self, input: i32) -> Action {
+        self.keys.get(&input).copied().unwrap_or(Action::Idle)
+    }
+
+    // The keys bound to the action, as they would be written in a key map
+    pub fn keys(&self, action: Action) -> Vec<String> {
+        let mut keys: Vec<String> = self
+            .keys
+            .iter()
+            .filter(|(_, bound)| **bound == action)
+            .filter_map(|(key, _)| name(*key))
+            .collect();
+        keys.sort();
+        keys
+    }
+}
+
+fn name(key: i32) -> Option<String> {
+    if let Some((name, _)) = NAMED_KEYS.iter().find(|(_, named)| *named == key) {
+        return Some(name.to_string());
+    }
+    char::from_u32(key as u32).map(String::from)
+}
+
+fn key(name: &str) -> Option<i32> {
+    if let Some((_, key)) = NAMED_KEYS.iter().find(|(named, _)| *named == name) {
+        return Some(*key);
+    }
+    let mut chars = name.chars();
+    match (chars.next(), chars.next()) {
+        (Some(char), None) if char.is_ascii_graphic() => Some(char as i32),
+        _ => None,
+    }
+}
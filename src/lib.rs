@@ -0,0 +1,76 @@
+//! The rules of the game, free of any terminal code.
+//!
+//! [`Logic`] owns the board and everything on it. A front end feeds it the player's
+//! moves with [`Logic::player_move`] and [`Logic::player_shoot`], then advances it one
+//! tick by calling [`Logic::fade`], [`Logic::generate`], [`Logic::shift`],
+//! [`Logic::handle`] and [`Logic::hit`] in that order, then lets the tick's worth of
+//! time go by with [`Logic::advance`]. Once the formation is gone, [`Logic::level_up`]
+//! brings in the next one.
+//!
+//! Everything on the board, from the player to each bullet in flight, is an [`Entity`]
+//! in a single store, [`Logic::entities`]. They share the same components whatever
+//! their [`Kind`], so a front end can draw them all the same way, and keep hold of an
+//! [`Id`] to follow a particular one from tick to tick.
+//!
+//! Entities only run into each other in [`Logic::hit`], which looks at the whole path each
+//! one took since the last tick rather than just where it ended up, so nothing moving
+//! fast or head-on slips through a target.
+//!
+//! What happened during a tick, from kills to lost ships, comes out as a list of
+//! [`event::Event`]s. Scoring, lives and any feedback are all up to whoever reads them.
+//!
+//! ```
+//! use space_invaders::{Direction, Logic, COLS, LINES};
+//! use std::time::Duration;
+//!
+//! let mut logic = Logic::new(LINES, COLS)?;
+//! let mut level = 0;
+//! logic.level_up(&mut level);
+//! logic.player_move(&Direction::Left);
+//! logic.player_shoot();
+//! logic.fade();
+//! logic.generate();
+//! logic.shift();
+//! logic.handle();
+//! logic.hit();
+//! let score: i32 = logic.events().iter().map(|event| event.points(level)).sum();
+//! assert_eq!(score, 0);
+//! logic.events_reset();
+//! logic.advance(Duration::from_millis(50));
+//! # Ok::<(), space_invaders::Error>(())
+//! ```
+
+#![warn(missing_docs)]
+
+mod achievement;
+pub mod difficulty;
+pub mod direction;
+mod elapsed;
+pub mod entity;
+pub mod error;
+pub mod event;
+mod flash;
+pub mod garbage;
+pub mod grid;
+pub mod logic;
+pub mod object;
+pub mod power;
+pub mod role;
+pub mod script;
+pub mod sprite;
+mod util;
+
+pub use achievement::Achievement;
+pub use difficulty::Difficulty;
+pub use direction::Direction;
+pub use entity::{Entities, Entity, Id, Kind, Team};
+pub use error::Error;
+pub use flash::Flash;
+pub use logic::Logic;
+pub use object::Object;
+pub use power::Effect;
+
+/// Height of the board, borders included.
+pub const LINES: i32 = 24;
+/// Width of the board, borders included.
+pub const COLS: i32 = 40;
@@ -0,0 +1,675 @@
+//! What each power-up effect does, kept in one place per effect.
+//!
+//! The rules call on an [`EffectHandler`] at every point an effect can change the game:
+//! when its power-up is collected and when it runs out, and, while it lasts, as the board
+//! fills up, as the player shoots and moves, as the enemy side fires and moves, and as
+//! things run into each other. A [`Registry`] holds the handler of every effect, and
+//! [`Logic::with_handler`] swaps one in for a game, so that the built-in behavior of that
+//! effect is gone altogether.
+
+use super::{
+    handle::Handle, hit::Hit, Logic, ATTACK_COOLDOWN, DOUBLE_ATTACK_COOLDOWN, OBSTACLES,
+    POWER_COOLDOWN, SHIELDS, TRIPLE_ATTACK_COOLDOWN, YIELDS,
+};
+use crate::{
+    achievement::Achievement,
+    direction::Direction,
+    entity::{Entity, Id, Kind, Team},
+    event::{Cause, Event, Weapon},
+    flash::{Burst, Flash},
+    grid::Grid,
+    object::Object,
+    power::Effect,
+    role::Role,
+    sprite::Sprite,
+    util,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::Duration,
+};
+
+/// A trigger pull, which the effects going on at the time get to shape.
+#[derive(Clone, Debug)]
+pub struct Shot {
+    /// How long the gun has to rest since the last shot before this one goes off.
+    pub reload: Duration,
+    /// How long the gun has to rest after this shot.
+    pub cooldown: Duration,
+    /// Where each bullet sets off from and where it heads, the player's own first.
+    pub bullets: Vec<((i32, i32), Direction)>,
+    /// Whether the bullets burst into shrapnel when they hit an enemy.
+    pub explosive: bool,
+    /// Whether the bullets fly the opposite way.
+    pub reversed: bool,
+    /// Whether it counts as a plain shot, which a wave cleared without any rewards.
+    pub plain: bool,
+}
+
+/// How far the enemy side gets to move on a tick.
+#[derive(Clone, Copy, Debug)]
+pub struct March {
+    /// Whether lasers, the boss and the formation move at all.
+    pub foes: bool,
+    /// Whether the formation turns and steps.
+    pub formation: bool,
+    /// Whether the formation steps down once it reaches an edge.
+    pub descend: bool,
+}
+
+impl Default for March {
+    fn default() -> Self {
+        Self {
+            foes: true,
+            formation: true,
+            descend: true,
+        }
+    }
+}
+
+/// What the player's bullets do to the enemies they hit on a tick.
+#[derive(Clone, Copy, Debug)]
+pub struct Strike {
+    /// Whether the enemies hit go down.
+    pub lethal: bool,
+    /// Whether the bullets stop there, bursting if they are explosive.
+    pub spent: bool,
+}
+
+impl Default for Strike {
+    fn default() -> Self {
+        Self {
+            lethal: true,
+            spent: true,
+        }
+    }
+}
+
+/// The behavior of an [`Effect`]. Every hook does nothing by default, except that
+/// collecting the power-up starts the effect's timer.
+///
+/// Only [`EffectHandler::on_collect`] and [`EffectHandler::on_expire`] are called
+/// whatever the effect's timer says, the other hooks only while the effect lasts. Hooks
+/// are listed in the order a tick calls them.
+pub trait EffectHandler: Send + Sync {
+    /// The player shot the power-up, which was at `pos`.
+    fn on_collect(&self, logic: &mut Logic, effect: Effect, _pos: (i32, i32)) {
+        logic.effects.insert(effect, Duration::ZERO);
+    }
+
+    /// The player pulls the trigger. The shot only goes off if the gun has rested long
+    /// enough once every effect has had its say.
+    fn modify_shot(&self, _logic: &mut Logic, _shot: &mut Shot) {}
+
+    /// The player steps towards `direction`. Returns whether the handler moved the
+    /// player itself, instead of the usual step.
+    fn on_player_move(&self, _logic: &mut Logic, _direction: &Direction) -> bool {
+        false
+    }
+
+    /// The enemies are about to open fire. Returns whether they may.
+    fn on_enemy_fire(&self, _logic: &mut Logic) -> bool {
+        true
+    }
+
+    /// Once a tick, after the enemies fired, to put the effect's own things on the board.
+    fn on_generate(&self, _logic: &mut Logic) {}
+
+    /// One of the player's bullets just flew off the board.
+    fn on_bullet_out(&self, _bullet: &mut Entity) {}
+
+    /// The enemy side is about to move.
+    fn modify_enemy_move(&self, _logic: &mut Logic, _march: &mut March) {}
+
+    /// The effect just ran out.
+    fn on_expire(&self, _logic: &mut Logic) {}
+
+    /// Once a tick, once everything has moved and the effects that ran out are over.
+    fn on_tick(&self, _logic: &mut Logic) {}
+
+    /// The enemy lasers may have reached the player's side, before the player's bullets
+    /// hit anything.
+    fn on_lasers(&self, _logic: &mut Logic) {}
+
+    /// The player's bullets just took out the boss.
+    fn on_boss_hit(&self, _logic: &mut Logic) {}
+
+    /// The player's bullets hit the `struck` enemies.
+    fn on_enemy_hit(&self, _logic: &mut Logic, _struck: &HashSet<Id>, _strike: &mut Strike) {}
+
+    /// The enemies left standing may have run into whatever the player put in their way.
+    fn on_enemy_contact(&self, _logic: &mut Logic) {}
+
+    /// Lasers fired by `shooters` reached the player. Returns whether the ship goes down.
+    fn on_player_hit(&self, _logic: &mut Logic, _shooters: &HashSet<Id>) -> bool {
+        true
+    }
+}
+
+/// The handler of every effect, the built-in ones by default. Effects without one of
+/// their own just last for a while.
+#[derive(Clone)]
+pub struct Registry {
+    // Handlers take turns in the order they were first registered
+    handlers: Vec<(Effect, Arc<dyn EffectHandler>)>,
+    fallback: Arc<dyn EffectHandler>,
+}
+
+impl Registry {
+    /// A registry where no effect does anything but last.
+    pub fn empty() -> Self {
+        Self {
+            handlers: vec![],
+            fallback: Arc::new(Lasting),
+        }
+    }
+
+    /// Makes `handler` the one in charge of `effect`, replacing whichever was.
+    pub fn register(&mut self, effect: Effect, handler: impl EffectHandler + 'static) {
+        let handler: Arc<dyn EffectHandler> = Arc::new(handler);
+        match self.handlers.iter_mut().find(|(other, _)| *other == effect) {
+            Some(registered) => registered.1 = handler,
+            None => self.handlers.push((effect, handler)),
+        }
+    }
+
+    /// The handler in charge of `effect`, or one doing nothing.
+    pub fn get(&self, effect: Effect) -> Arc<dyn EffectHandler> {
+        self.handlers
+            .iter()
+            .find(|(other, _)| *other == effect)
+            .map_or(&self.fallback, |(_, handler)| handler)
+            .clone()
+    }
+
+    // The handlers of the effects going on, in turn
+    pub(super) fn going(&self, effects: &HashMap<Effect, Duration>) -> Vec<Arc<dyn EffectHandler>> {
+        self.handlers
+            .iter()
+            .filter(|(effect, _)| Handle::power(effects, effect))
+            .map(|(_, handler)| handler.clone())
+            .collect()
+    }
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        let mut registry = Registry::empty();
+        // Some of them have to take turns in this order: the xerox is put on the board
+        // before the obstacles, shields and follower, the shields cover before the
+        // follower does and both before Block, Double fires before Triple, and the player
+        // jumps before diving
+        registry.register(Effect::Xerox, Xerox);
+        registry.register(Effect::Obstacle, Obstacle);
+        registry.register(Effect::Shield, Shield);
+        registry.register(Effect::Follower, Follower);
+        registry.register(Effect::Block, Block);
+        registry.register(Effect::Double, Double);
+        registry.register(Effect::Triple, Triple);
+        registry.register(Effect::Jump, Jump);
+        registry.register(Effect::Kamizake, Kamizake);
+        registry.register(Effect::Antigravity, Antigravity);
+        registry.register(Effect::Clear, Clear);
+        registry.register(Effect::Explode, Explode);
+        registry.register(Effect::Grenade, Grenade);
+        registry.register(Effect::Hijack, Hijack);
+        registry.register(Effect::Invincible, Invincible);
+        registry.register(Effect::Lock, Lock);
+        registry.register(Effect::Mindcontrol, Mindcontrol);
+        registry.register(Effect::Numb, Numb);
+        registry.register(Effect::Pierce, Pierce);
+        registry.register(Effect::Quickshot, Quickshot);
+        registry.register(Effect::Reflect, Reflect);
+        registry.register(Effect::Ultra, Ultra);
+        registry.register(Effect::Vendetta, Vendetta);
+        registry.register(Effect::Warp, Warp);
+        registry.register(Effect::Yield, Yield);
+        registry.register(Effect::Zombify, Zombify);
+        registry
+    }
+}
+
+struct Lasting;
+
+impl EffectHandler for Lasting {}
+
+struct Antigravity;
+
+impl EffectHandler for Antigravity {
+    fn modify_enemy_move(&self, _logic: &mut Logic, march: &mut March) {
+        march.descend = false;
+    }
+}
+
+struct Block;
+
+impl EffectHandler for Block {
+    fn on_lasers(&self, logic: &mut Logic) {
+        Hit::lasers(logic);
+    }
+}
+
+struct Clear;
+
+impl EffectHandler for Clear {
+    fn on_collect(&self, logic: &mut Logic, _effect: Effect, _pos: (i32, i32)) {
+        logic
+            .entities
+            .retain(|entity| entity.kind() != Kind::Bullet || entity.team() != Team::Enemy);
+    }
+}
+
+struct Double;
+
+impl EffectHandler for Double {
+    fn modify_shot(&self, logic: &mut Logic, shot: &mut Shot) {
+        let player_pos = logic.player().pos();
+        shot.bullets
+            .push(((player_pos.0 - 1, player_pos.1), Direction::Up));
+        shot.cooldown += DOUBLE_ATTACK_COOLDOWN - ATTACK_COOLDOWN;
+    }
+}
+
+struct Explode;
+
+impl EffectHandler for Explode {
+    // Goes off where the power-up was
+    fn on_collect(&self, logic: &mut Logic, _effect: Effect, bomb: (i32, i32)) {
+        let flashes = Flash::area(&bomb, 2, logic.size(), Burst::Blast);
+        logic.flashes.extend(flashes);
+        let gone = logic.entities.take(|entity| {
+            let pos = entity.pos();
+            let caught = (pos.0 >= bomb.0 - 2 && pos.0 <= bomb.0 + 2)
+                && (pos.1 >= bomb.1 - 2 && pos.1 <= bomb.1 + 2);
+            entity.kind() == Kind::Enemy && caught
+        });
+        logic.debris(&gone, Weapon::Explosion);
+    }
+}
+
+struct Follower;
+
+impl EffectHandler for Follower {
+    fn on_generate(&self, logic: &mut Logic) {
+        if logic.entities.first(Kind::Follower).is_none() {
+            let player_x = logic.player().pos().1;
+            logic.entities.spawn(Entity::new(
+                Kind::Follower,
+                (logic.height - 3, player_x),
+                Sprite::Shield,
+                Role::Player,
+                Team::Player,
+            ));
+        }
+    }
+
+    fn on_expire(&self, logic: &mut Logic) {
+        logic
+            .entities
+            .retain(|entity| entity.kind() != Kind::Follower);
+    }
+
+    // A single hit breaks it, and the effect along with it
+    fn on_lasers(&self, logic: &mut Logic) {
+        if logic.entities.first(Kind::Follower).is_some() {
+            Hit::cover(logic, Kind::Follower);
+            let broken = logic
+                .entities
+                .of(Kind::Follower)
+                .any(|follower| !follower.is_alive());
+            if broken {
+                logic
+                    .entities
+                    .retain(|entity| entity.kind() != Kind::Follower);
+                logic.effects.insert(Effect::Follower, POWER_COOLDOWN);
+            }
+        }
+    }
+}
+
+struct Grenade;
+
+impl EffectHandler for Grenade {
+    fn modify_shot(&self, _logic: &mut Logic, shot: &mut Shot) {
+        shot.explosive = true;
+    }
+}
+
+struct Hijack;
+
+impl EffectHandler for Hijack {
+    fn on_enemy_fire(&self, _logic: &mut Logic) -> bool {
+        false
+    }
+}
+
+struct Invincible;
+
+impl EffectHandler for Invincible {
+    fn on_player_hit(&self, _logic: &mut Logic, _shooters: &HashSet<Id>) -> bool {
+        false
+    }
+}
+
+struct Jump;
+
+impl EffectHandler for Jump {
+    fn modify_shot(&self, _logic: &mut Logic, shot: &mut Shot) {
+        shot.reversed = true;
+    }
+
+    fn on_expire(&self, logic: &mut Logic) {
+        if !Handle::power(&logic.effects, &Effect::Kamizake) {
+            Handle::land(logic);
+        }
+    }
+
+    fn on_tick(&self, logic: &mut Logic) {
+        logic.player_mut().set_row(3);
+    }
+
+    fn on_boss_hit(&self, logic: &mut Logic) {
+        logic.feats.push(Achievement::Leapfrog);
+    }
+}
+
+struct Kamizake;
+
+impl EffectHandler for Kamizake {
+    fn on_expire(&self, logic: &mut Logic) {
+        if !Handle::power(&logic.effects, &Effect::Jump) {
+            Handle::land(logic);
+        }
+    }
+
+    // The player dives across the board, taking out the whole formation if it gets there
+    fn on_tick(&self, logic: &mut Logic) {
+        let (height, dir) = if Handle::power(&logic.effects, &Effect::Jump) {
+            (3, &Direction::Down)
+        } else {
+            (logic.height - 2, &Direction::Up)
+        };
+        let new_pos = logic.player().new_pos(dir);
+        if util::out_of_bounds(new_pos, logic.size()) {
+            logic.wreck(Cause::Crash);
+            logic.player_mut().set_row(height);
+        } else {
+            let size = logic.size();
+            logic.player_mut().shift(dir, size);
+            let player = logic.player();
+            let exterminate = logic.enemies().any(|enemy| enemy.crosses(player));
+            if exterminate {
+                let gone = logic.entities.take(|entity| entity.kind() == Kind::Enemy);
+                logic.debris(&gone, Weapon::Kamizake);
+                logic.events.push(Event::PlayerHit { cause: Cause::Ram });
+                logic.player_mut().set_row(height);
+                logic.feats.push(Achievement::Banzai);
+            }
+        }
+    }
+}
+
+struct Lock;
+
+impl EffectHandler for Lock {
+    fn modify_enemy_move(&self, _logic: &mut Logic, march: &mut March) {
+        march.formation = false;
+    }
+}
+
+struct Mindcontrol;
+
+impl EffectHandler for Mindcontrol {
+    fn on_expire(&self, logic: &mut Logic) {
+        Handle::release(logic);
+    }
+
+    fn on_enemy_hit(&self, logic: &mut Logic, struck: &HashSet<Id>, strike: &mut Strike) {
+        for id in struck {
+            if let Some(enemy) = logic.entities.get_mut(*id) {
+                enemy.set_team(Team::Player);
+            }
+        }
+        strike.lethal = false;
+    }
+}
+
+struct Numb;
+
+impl EffectHandler for Numb {
+    fn on_enemy_hit(&self, logic: &mut Logic, struck: &HashSet<Id>, strike: &mut Strike) {
+        for id in struck {
+            if let Some(enemy) = logic.entities.get_mut(*id) {
+                enemy.set_numb();
+            }
+        }
+        strike.lethal = false;
+    }
+}
+
+struct Obstacle;
+
+impl EffectHandler for Obstacle {
+    fn on_generate(&self, logic: &mut Logic) {
+        if logic.entities.first(Kind::Obstacle).is_none() {
+            for i in 0..OBSTACLES {
+                let pos = (logic.height - (4 + 2 * i), logic.width / 2);
+                let obstacle = Entity::new(
+                    Kind::Obstacle,
+                    pos,
+                    Sprite::Obstacle,
+                    Role::Obstacle,
+                    Team::Player,
+                );
+                logic.entities.spawn(obstacle.with_health(3));
+            }
+        }
+    }
+
+    fn on_expire(&self, logic: &mut Logic) {
+        logic
+            .entities
+            .retain(|entity| entity.kind() != Kind::Obstacle);
+    }
+
+    // Enemies running into an obstacle are crushed, and wear it down
+    fn on_enemy_contact(&self, logic: &mut Logic) {
+        let grid = Grid::new(logic.entities.of(Kind::Obstacle));
+        let mut crushed = HashSet::new();
+        let mut damage = vec![];
+        for enemy in logic.enemies() {
+            let obstacles = grid.crossing(enemy);
+            if !obstacles.is_empty() {
+                crushed.insert(enemy.id());
+            }
+            damage.extend(obstacles);
+        }
+        Hit::damage(logic, &damage);
+        let gone = logic.entities.take(|entity| crushed.contains(&entity.id()));
+        logic.debris(&gone, Weapon::Obstacle);
+        logic
+            .entities
+            .retain(|entity| entity.kind() != Kind::Obstacle || entity.is_alive());
+    }
+}
+
+struct Pierce;
+
+impl EffectHandler for Pierce {
+    fn modify_shot(&self, _logic: &mut Logic, shot: &mut Shot) {
+        shot.plain = false;
+    }
+
+    fn on_enemy_hit(&self, _logic: &mut Logic, _struck: &HashSet<Id>, strike: &mut Strike) {
+        strike.spent = false;
+    }
+}
+
+struct Quickshot;
+
+impl EffectHandler for Quickshot {
+    fn modify_shot(&self, _logic: &mut Logic, shot: &mut Shot) {
+        shot.reload /= 2;
+    }
+}
+
+struct Reflect;
+
+impl EffectHandler for Reflect {
+    fn on_bullet_out(&self, bullet: &mut Entity) {
+        let new_dir = match bullet.velocity() {
+            Some(Direction::Up) => Direction::Down,
+            Some(Direction::Down) => Direction::Up,
+            Some(Direction::LeftUp) => Direction::RightUp,
+            Some(Direction::RightUp) => Direction::LeftUp,
+            _ => Direction::Up,
+        };
+        bullet.set_velocity(new_dir);
+        bullet.advance();
+    }
+}
+
+struct Shield;
+
+impl EffectHandler for Shield {
+    fn on_generate(&self, logic: &mut Logic) {
+        if logic.entities.first(Kind::Shield).is_none() {
+            // Narrow boards only fit some of them
+            let cols = (0..SHIELDS).map(|i| 3 * i + 1);
+            for col in cols.take_while(|col| *col < logic.width - 1) {
+                let pos = (logic.height - 3, col);
+                let shield = Entity::new(
+                    Kind::Shield,
+                    pos,
+                    Sprite::Shield,
+                    Role::Shield,
+                    Team::Player,
+                );
+                logic.entities.spawn(shield.with_health(3));
+            }
+        }
+    }
+
+    fn on_expire(&self, logic: &mut Logic) {
+        logic
+            .entities
+            .retain(|entity| entity.kind() != Kind::Shield);
+    }
+
+    fn on_lasers(&self, logic: &mut Logic) {
+        Hit::cover(logic, Kind::Shield);
+        logic
+            .entities
+            .retain(|entity| entity.kind() != Kind::Shield || entity.is_alive());
+    }
+}
+
+struct Triple;
+
+impl EffectHandler for Triple {
+    fn modify_shot(&self, logic: &mut Logic, shot: &mut Shot) {
+        let player_pos = logic.player().pos();
+        shot.bullets
+            .push(((player_pos.0 - 1, player_pos.1 + 1), Direction::LeftUp));
+        shot.bullets
+            .push(((player_pos.0 - 1, player_pos.1 - 1), Direction::RightUp));
+        shot.cooldown += TRIPLE_ATTACK_COOLDOWN - ATTACK_COOLDOWN;
+    }
+}
+
+struct Ultra;
+
+impl EffectHandler for Ultra {
+    fn on_collect(&self, logic: &mut Logic, _effect: Effect, _pos: (i32, i32)) {
+        let player_pos = logic.player().pos();
+        // When jumping, direction is reversed so we don't need to worry about changing direction,
+        // only the range
+        let range = if Handle::power(&logic.effects, &Effect::Jump) {
+            player_pos.0 + 1..logic.height - 1
+        } else {
+            3..player_pos.0
+        };
+        for i in range {
+            let dir = logic.aim(Direction::Up);
+            logic.fire((i, player_pos.1), dir, false, Sprite::Ultra, Role::Ultra);
+            logic
+                .flashes
+                .push(Flash::new((i, player_pos.1), Burst::Beam));
+        }
+    }
+}
+
+struct Vendetta;
+
+impl EffectHandler for Vendetta {
+    fn on_player_hit(&self, logic: &mut Logic, shooters: &HashSet<Id>) -> bool {
+        let gone = logic
+            .entities
+            .take(|entity| shooters.contains(&entity.id()));
+        logic.debris(&gone, Weapon::Vendetta);
+        true
+    }
+}
+
+struct Warp;
+
+impl EffectHandler for Warp {
+    // Stepping off one side of the board comes back in on the other
+    fn on_player_move(&self, logic: &mut Logic, direction: &Direction) -> bool {
+        let (size, width) = (logic.size(), logic.width);
+        let player = logic.player_mut();
+        if !util::out_of_bounds(player.new_pos(direction), size) {
+            return false;
+        }
+        match direction {
+            Direction::Left => player.set_col(width - 1 - player.extent().1),
+            Direction::Right => player.set_col(1),
+            _ => return false,
+        }
+        true
+    }
+}
+
+struct Xerox;
+
+impl EffectHandler for Xerox {
+    fn on_generate(&self, logic: &mut Logic) {
+        if logic.entities.first(Kind::Xerox).is_none() {
+            let pos = (logic.player().pos().0, logic.mirror());
+            logic.entities.spawn(Entity::new(
+                Kind::Xerox,
+                pos,
+                Sprite::Player,
+                Role::Bullet,
+                Team::Player,
+            ));
+        }
+    }
+
+    fn on_expire(&self, logic: &mut Logic) {
+        logic.entities.retain(|entity| entity.kind() != Kind::Xerox);
+    }
+}
+
+struct Yield;
+
+impl EffectHandler for Yield {
+    fn on_collect(&self, logic: &mut Logic, _effect: Effect, _pos: (i32, i32)) {
+        logic.yield_counter = YIELDS;
+    }
+}
+
+struct Zombify;
+
+impl EffectHandler for Zombify {
+    // The enemy side only moves every other tick
+    fn modify_enemy_move(&self, logic: &mut Logic, march: &mut March) {
+        logic.slow_down = !logic.slow_down;
+        march.foes &= !logic.slow_down;
+    }
+
+    fn on_expire(&self, logic: &mut Logic) {
+        logic.slow_down = false;
+    }
+}
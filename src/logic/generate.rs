@@ -1,19 +1,12 @@
-use super::{
-    handle::Handle, Logic, BOSS_PROPABILITY, ENEMIES_PER_ROW, ENEMY_ROWS, FIRE_PROBABILITY,
-    OBSTACLES, POWER_PROBABILITY, SHIELDS,
-};
-use super::{
-    CHAR_BULLET, CHAR_ENEMY, CHAR_FOLLOWER, CHAR_LASER, CHAR_OBSTACLE, CHAR_PLAYER, CHAR_SHIELD,
-    COLOR_ALLY, COLOR_BULLET, COLOR_ENEMY, COLOR_FOLLOWER, COLOR_LASER, COLOR_OBSTACLES,
-    COLOR_SHIELDS,
-};
+use super::{Logic, BOSS_PROPABILITY, ENEMIES_PER_ROW, ENEMY_ROWS};
 use crate::object::Object;
 use crate::{
-    boss::Boss,
     direction::Direction,
-    power::{Effect, PowerUp},
-    shield::Shield,
-    shooter::Shooter,
+    entity::{Entity, Kind, Team},
+    event::Event,
+    power::Effect,
+    role::Role,
+    sprite::Sprite,
     util,
 };
 use rand::Rng;
@@ -21,106 +14,99 @@ use rand::Rng;
 pub struct Generate;
 
 impl Generate {
+    fn enemy(pos: (i32, i32)) -> Entity {
+        Entity::new(Kind::Enemy, pos, Sprite::Enemy, Role::Enemy, Team::Enemy)
+    }
+
     pub fn enemies(logic: &mut Logic) {
         for j in 0..ENEMY_ROWS {
             for i in 0..ENEMIES_PER_ROW {
-                logic.enemies.push(Shooter::new(
-                    (2 * (j + 2), 2 * i + 1),
-                    CHAR_ENEMY,
-                    COLOR_ENEMY,
-                ));
-            }
-        }
-    }
-
-    pub fn shields(logic: &mut Logic) {
-        let shield = Handle::power(&logic.effects, &Effect::Shield);
-        if shield && logic.shields.is_empty() {
-            for i in 0..SHIELDS {
-                logic.shields.push(Shield::new(
-                    (logic.height - 3, 3 * i + 1),
-                    3,
-                    COLOR_SHIELDS,
-                    CHAR_SHIELD,
-                ))
+                logic
+                    .entities
+                    .spawn(Generate::enemy((2 * (j + 2), 2 * i + 1)));
             }
         }
     }
 
-    pub fn obstacles(logic: &mut Logic) {
-        let obstacle = Handle::power(&logic.effects, &Effect::Obstacle);
-        if obstacle && logic.obstacles.is_empty() {
-            for i in 0..OBSTACLES {
-                logic.obstacles.push(Shield::new(
-                    (logic.height - (4 + 2 * i), logic.width / 2),
-                    3,
-                    COLOR_OBSTACLES,
-                    CHAR_OBSTACLE,
-                ));
+    pub fn reinforcements(logic: &mut Logic) {
+        let top = logic.enemies().map(|enemy| enemy.pos().0).min();
+        let left = logic.enemies().map(|enemy| enemy.pos().1).min();
+        if let (Some(top), Some(left)) = (top, left) {
+            let x = (top - 2).max(2);
+            let row: Vec<(i32, i32)> = (0..ENEMIES_PER_ROW)
+                .map(|i| (x, left + 2 * i))
+                .filter(|pos| !util::out_of_bounds(*pos, logic.size()))
+                .filter(|pos| logic.enemies().all(|enemy| !enemy.covers(*pos)))
+                .collect();
+            for pos in row {
+                logic.entities.spawn(Generate::enemy(pos));
             }
         }
     }
 
-    pub fn xerox(logic: &mut Logic) {
-        let xerox = Handle::power(&logic.effects, &Effect::Xerox);
-        if logic.xerox.is_none() && xerox {
-            let player_pos = logic.player.pos();
-            let y = logic.width - player_pos.1;
-            logic.xerox = Some(Shooter::new((player_pos.0, y), CHAR_PLAYER, COLOR_ALLY));
-            if let Some(xerox) = &mut logic.xerox {
-                xerox.mind_control();
-            }
-        }
-        if !xerox {
-            logic.xerox = None;
+    pub fn boss(logic: &mut Logic) {
+        if util::random_event(&mut logic.rng, BOSS_PROPABILITY) && logic.boss().is_none() {
+            let boss = Entity::new(Kind::Boss, (2, 1), Sprite::Boss, Role::Boss, Team::Enemy);
+            logic.entities.spawn(boss.with_velocity(Direction::Right));
+            logic.events.push(Event::BossSpawned);
         }
     }
 
-    pub fn follower(logic: &mut Logic) {
-        let follower = Handle::power(&logic.effects, &Effect::Follower);
-        if Handle::power(&logic.effects, &Effect::Follower) && logic.follower.is_none() {
-            let player_x = logic.player().pos().1;
-            logic.follower = Some(Shield::new(
-                (logic.height - 3, player_x),
-                1,
-                COLOR_FOLLOWER,
-                CHAR_FOLLOWER,
-            ));
-        }
-        if !follower {
-            logic.follower = None;
+    pub fn power(logic: &mut Logic) {
+        if util::random_event(&mut logic.rng, logic.difficulty.power_probability()) {
+            let pos = Generate::spot(logic);
+            let effect: Effect = logic.rng.gen();
+            Generate::pickup(logic, pos, Kind::Power(effect), effect.initial());
         }
     }
 
-    pub fn boss(logic: &mut Logic) {
-        if util::random_event(BOSS_PROPABILITY) && logic.boss.is_none() {
-            logic.boss = Some(Boss::new((2, 1)));
-        }
+    // Anywhere on the board a power-up may turn up
+    pub fn spot(logic: &mut Logic) -> (i32, i32) {
+        let y = logic.rng.gen_range(2..logic.height - 2);
+        let x = logic.rng.gen_range(1..logic.width - 1);
+        (y, x)
     }
 
-    pub fn power(logic: &mut Logic) {
-        if util::random_event(POWER_PROBABILITY) {
-            let mut rng = rand::thread_rng();
-            let y = rng.gen_range(2..logic.height - 2);
-            let x = rng.gen_range(1..logic.width - 1);
-            logic.powers.push(PowerUp::new((y, x), rand::random()));
-        }
+    pub fn pickup(logic: &mut Logic, pos: (i32, i32), kind: Kind, glyph: char) {
+        logic.entities.spawn(Entity::new(
+            kind,
+            pos,
+            Sprite::Glyph(glyph),
+            Role::Power,
+            Team::Neutral,
+        ));
     }
 
     pub fn enemy_attack(logic: &mut Logic) {
-        if !Handle::power(&logic.effects, &Effect::Hijack) {
-            for enemy in logic.enemies.iter_mut() {
-                if util::random_event(FIRE_PROBABILITY) {
-                    if enemy.is_mind_controlled() {
-                        logic.player.shoot_pos(
-                            &enemy.pos(),
-                            rand::random(),
+        let mut fire = true;
+        for handler in logic.handlers() {
+            fire &= handler.on_enemy_fire(logic);
+        }
+        if fire {
+            let enemies: Vec<Entity> = logic.enemies().cloned().collect();
+            for enemy in enemies {
+                if util::random_event(&mut logic.rng, logic.difficulty.fire_probability()) {
+                    if enemy.team() == Team::Player {
+                        let dir = logic.rng.gen();
+                        let dir = logic.aim(dir);
+                        logic.fire_as(
+                            enemy.id(),
+                            enemy.pos(),
+                            dir,
                             false,
-                            CHAR_BULLET,
-                            COLOR_BULLET,
+                            Sprite::Bullet,
+                            Role::Bullet,
                         );
                     } else {
-                        enemy.shoot(Direction::Down, false, CHAR_LASER, COLOR_LASER);
+                        let laser = Entity::new(
+                            Kind::Bullet,
+                            enemy.pos(),
+                            Sprite::Laser,
+                            Role::Laser,
+                            Team::Enemy,
+                        );
+                        let laser = laser.with_velocity(Direction::Down).with_owner(enemy.id());
+                        logic.entities.spawn(laser);
                     }
                 }
             }
@@ -1,238 +1,191 @@
-use super::YIELDS;
-use super::{
-    handle::Handle, Logic, BOSS_SCORE, CHAR_LASER, COLOR_LASER, ENEMY_SCORE, POWER_COOLDOWN,
-};
+use super::{effects::Strike, handle::Handle, Logic};
 use crate::object::Object;
-use crate::{bullet::Bullet, power::Effect};
-use std::time::{Duration, Instant};
+use crate::{
+    entity::{Entity, Id, Kind, Team},
+    event::{Event, Weapon},
+    flash::{Burst, Flash},
+    grid::Grid,
+    role::Role,
+    sprite::Sprite,
+};
+use rand::Rng;
+use std::collections::HashSet;
+use std::time::Duration;
 
 pub struct Hit;
 
 impl Hit {
     pub fn player(logic: &mut Logic) -> bool {
-        let enemies_copy = logic.enemies.to_vec();
-
-        if Handle::power(&logic.effects, &Effect::Vendetta) {
-            logic.enemies.retain(|enemy| {
-                let mut retain = true;
-                for bullet in enemy.bullets() {
-                    if bullet.pos() == logic.player.pos() {
-                        retain = false;
-                    }
-                }
-                retain
-            })
-        }
-
-        if !Handle::power(&logic.effects, &Effect::Invincible) {
-            for enemy in enemies_copy {
-                for bullet in enemy.bullets() {
-                    if bullet.pos() == logic.player.pos() {
-                        return true;
-                    }
-                }
+        let player = logic.player();
+        let lasers: HashSet<Id> = logic
+            .entities
+            .bullets(Team::Enemy)
+            .filter(|laser| laser.crosses(player))
+            .map(Entity::id)
+            .collect();
+        let shooters: HashSet<Id> = lasers
+            .iter()
+            .filter_map(|laser| logic.entities.get(*laser)?.owner())
+            .collect();
+        // A laser stops at the player, or it would run into them again on its way out
+        logic.entities.despawn_all(&lasers);
+
+        let mut wrecked = !lasers.is_empty();
+        if wrecked {
+            // Every effect gets its say, even once one has spared the ship
+            for handler in logic.handlers() {
+                wrecked &= handler.on_player_hit(logic, &shooters);
             }
         }
-        false
+        wrecked
     }
 
-    pub fn obstacles(logic: &mut Logic) {
-        if let Some(time) = logic.effects.get(&Effect::Obstacle) {
-            if time.elapsed() > POWER_COOLDOWN {
-                logic.obstacles.clear();
-            } else {
-                for enemy in &logic.enemies {
-                    for obstacle in logic.obstacles.iter_mut() {
-                        if enemy.pos() == obstacle.pos() {
-                            obstacle.damage();
-                        }
-                    }
-                }
-                for obstacle in &logic.obstacles {
-                    logic.enemies.retain(|enemy| enemy.pos() != obstacle.pos());
-                }
-                logic.obstacles.retain(|shield| shield.is_alive());
+    pub fn damage(logic: &mut Logic, ids: &[Id]) {
+        for id in ids {
+            if let Some(entity) = logic.entities.get_mut(*id) {
+                entity.damage();
             }
         }
     }
 
     pub fn powers(logic: &mut Logic) {
-        let bullets = logic.player.bullets().to_owned();
-        for bullet in bullets {
-            logic.powers.retain(|power| {
-                if power.pos() != bullet.pos() {
-                    true
-                } else {
-                    let effect = *power.effect();
-                    if effect == Effect::Clear {
-                        Handle::clear(&mut logic.enemies);
-                    } else if effect == Effect::Ultra {
-                        Handle::ultra(&mut logic.player, &logic.effects, &logic.height);
-                    } else if effect == Effect::Yield {
-                        logic.yield_counter = YIELDS;
-                    } else if effect == Effect::Explode {
-                        Handle::explode(&power.pos(), &mut logic.enemies);
-                    } else {
-                        logic.effects.insert(effect, Instant::now());
-                    }
-                    false
-                }
-            });
-        }
-    }
-
-    pub fn shields(logic: &mut Logic) {
-        if let Some(time) = logic.effects.get(&Effect::Shield) {
-            if time.elapsed() > POWER_COOLDOWN {
-                logic.shields.clear();
-            } else {
-                for enemy in &logic.enemies {
-                    for bullet in enemy.bullets() {
-                        for shield in logic.shields.iter_mut() {
-                            if bullet.pos() == shield.pos() {
-                                shield.damage();
-                            }
-                        }
-                    }
-                }
-                for shield in &logic.shields {
-                    for enemy in logic.enemies.iter_mut() {
-                        enemy
-                            .bullets_mut()
-                            .retain(|bullet| bullet.pos() != shield.pos());
-                    }
+        let grid = Grid::new(logic.entities.bullets(Team::Player));
+        let collected = logic.entities.take(|entity| {
+            matches!(entity.kind(), Kind::Power(_) | Kind::Custom(_)) && grid.crosses(entity)
+        });
+        for power in collected {
+            match power.kind() {
+                Kind::Power(effect) => {
+                    logic.events.push(Event::PowerCollected(effect));
+                    Handle::apply(logic, effect, &power.pos());
                 }
-                logic.shields.retain(|shield| shield.is_alive());
+                // Up to whoever dropped it
+                Kind::Custom(custom) => logic.events.push(Event::CustomCollected(custom)),
+                _ => {}
             }
         }
     }
 
-    pub fn follower(logic: &mut Logic) {
-        if let Some(follower) = logic.follower.as_mut() {
-            for enemy in &logic.enemies {
-                for bullet in enemy.bullets() {
-                    if bullet.pos() == follower.pos() {
-                        follower.damage();
-                    }
-                }
-            }
-            for enemy in logic.enemies.iter_mut() {
-                enemy
-                    .bullets_mut()
-                    .retain(|bullet| bullet.pos() != follower.pos());
-            }
-            if !follower.is_alive() {
-                logic.follower = None;
-                logic
-                    .effects
-                    .insert(Effect::Follower, Instant::now() - Duration::from_secs(10));
+    // Whatever of `kind` the enemy lasers run into takes the hit in their place
+    pub fn cover(logic: &mut Logic, kind: Kind) {
+        let grid = Grid::new(logic.entities.of(kind));
+        let mut blocked = HashSet::new();
+        let mut damage = vec![];
+        for laser in logic.entities.bullets(Team::Enemy) {
+            let covers = grid.crossing(laser);
+            if !covers.is_empty() {
+                blocked.insert(laser.id());
             }
+            damage.extend(covers);
         }
+        Hit::damage(logic, &damage);
+        logic.entities.despawn_all(&blocked);
     }
 
     pub fn boss(logic: &mut Logic) -> bool {
-        if let Some(boss) = logic.boss {
-            for bullet in logic.player.bullets() {
-                if bullet.pos() == boss.pos() {
-                    logic.boss = None;
-                    return true;
-                }
+        if let Some(boss) = logic.boss() {
+            let hit = logic
+                .entities
+                .bullets(Team::Player)
+                .any(|bullet| bullet.crosses(boss));
+            if hit {
+                let flashes = Flash::cover(boss, logic.size(), Burst::Explosion);
+                let id = boss.id();
+                logic.flashes.extend(flashes);
+                logic.events.push(Event::BossKilled);
+                logic.entities.despawn(id);
+                return true;
             }
         }
         false
     }
 
-    pub fn enemies(logic: &mut Logic) -> usize {
-        let previous_size = logic.enemies.len();
-        let enemies_copy = logic.enemies.to_vec();
-        let mind_control = Handle::power(&logic.effects, &Effect::Mindcontrol);
-        let numb = Handle::power(&logic.effects, &Effect::Numb);
-        let player_bullets_copy = logic.player.bullets().clone();
-        let mut exploding_bullets: Vec<Bullet> = vec![];
-        for bullet in player_bullets_copy {
-            for enemy in logic.enemies_mut() {
-                if enemy.pos() == bullet.pos() {
-                    if mind_control {
-                        enemy.mind_control();
-                    }
-                    if numb {
-                        enemy.set_numb();
-                    }
-                }
+    pub fn enemies(logic: &mut Logic) {
+        let grid = Grid::new(logic.enemies());
+
+        let mut struck = HashSet::new();
+        let mut spent = HashSet::new();
+        let mut exploding_bullets = vec![];
+        for bullet in logic.entities.bullets(Team::Player) {
+            let enemies = grid.crossing(bullet);
+            if enemies.is_empty() {
+                continue;
             }
-            if !mind_control && !numb {
-                logic.enemies.retain(|enemy| enemy.pos() != bullet.pos());
+            struck.extend(enemies);
+            spent.insert(bullet.id());
+            if bullet.is_explosive() {
+                exploding_bullets.push(bullet.pos());
             }
         }
 
         // With the Effect::Numb, enemies may collide with each other
-        logic.enemies_mut().retain(|other| {
-            let mut count = 0;
-            for enemy in &enemies_copy {
-                if other.pos() == enemy.pos() {
-                    count += 1;
-                }
-            }
-            count == 1
-        });
-
-        if !Handle::power(&logic.effects, &Effect::Pierce) {
-            for enemy in &enemies_copy {
-                logic.player.bullets_mut().retain(|bullet| {
-                    if bullet.pos() == enemy.pos() {
-                        exploding_bullets.push(bullet.clone());
-                        false
-                    } else {
-                        true
-                    }
-                });
+        let collided: HashSet<Id> = logic
+            .enemies()
+            .filter(|enemy| grid.crosses(enemy))
+            .map(Entity::id)
+            .collect();
+
+        let mut strike = Strike::default();
+        if !struck.is_empty() {
+            for handler in logic.handlers() {
+                handler.on_enemy_hit(logic, &struck, &mut strike);
             }
         }
-
-        for bullet in exploding_bullets {
-            if bullet.is_explosive() {
-                logic.player.shoot_pos(
-                    &bullet.pos(),
-                    rand::random(),
-                    false,
-                    CHAR_LASER,
-                    COLOR_LASER,
-                );
-            }
+        if strike.lethal {
+            let gone = logic.entities.take(|entity| struck.contains(&entity.id()));
+            logic.debris(&gone, Weapon::Bullet);
         }
+        let gone = logic
+            .entities
+            .take(|entity| collided.contains(&entity.id()));
+        logic.debris(&gone, Weapon::Collision);
 
-        let new_size = logic.enemies.len();
-        previous_size - new_size
+        if !strike.spent {
+            return;
+        }
+        logic.entities.despawn_all(&spent);
+        for pos in exploding_bullets {
+            logic
+                .flashes
+                .extend(Flash::area(&pos, 1, logic.size(), Burst::Blast));
+            let dir = logic.rng.gen();
+            let dir = logic.aim(dir);
+            logic.fire(pos, dir, false, Sprite::Laser, Role::Laser);
+        }
     }
 
     pub fn lasers(logic: &mut Logic) {
-        let enemies_copy = logic.enemies.to_vec();
-
-        for bullet in logic.player.bullets_mut() {
-            for enemy in logic.enemies.iter_mut() {
-                enemy
-                    .bullets_mut()
-                    .retain(|laser| laser.pos() != bullet.pos());
+        let grid = Grid::new(logic.entities.bullets(Team::Enemy));
+        let mut blocked = HashSet::new();
+        for bullet in logic.entities.bullets(Team::Player) {
+            let lasers = grid.crossing(bullet);
+            if !lasers.is_empty() {
+                blocked.insert(bullet.id());
+                blocked.extend(lasers);
             }
         }
+        logic.entities.despawn_all(&blocked);
+    }
 
-        for enemy in enemies_copy {
-            for laser in enemy.bullets() {
-                logic
-                    .player
-                    .bullets_mut()
-                    .retain(|bullet| bullet.pos() != laser.pos())
+    pub fn targets(logic: &mut Logic) {
+        let previous_events = logic.events.len();
+        if Hit::boss(logic) {
+            for handler in logic.handlers() {
+                handler.on_boss_hit(logic);
             }
         }
+        Hit::enemies(logic);
+        let kills = logic.events[previous_events..]
+            .iter()
+            .filter(|event| event.scores())
+            .count() as i32;
+        Hit::combo(logic, kills);
     }
 
-    pub fn targets(logic: &mut Logic, level: &i32) {
-        if Handle::power(&logic.effects, &Effect::Block) {
-            Hit::lasers(logic);
-        }
-        if Hit::boss(logic) {
-            logic.score_increment += BOSS_SCORE * level;
+    fn combo(logic: &mut Logic, kills: i32) {
+        if kills > 0 {
+            logic.combo = kills + logic.combo();
+            logic.last_kill = Some(Duration::ZERO);
         }
-        logic.score_increment += (Hit::enemies(logic) as i32) * ENEMY_SCORE * level;
     }
 }
@@ -1,222 +1,530 @@
+//! The rules of the game and the board they play out on.
+
+pub mod effects;
 mod generate;
 mod handle;
 mod hit;
 mod shift;
 
+use self::effects::{EffectHandler, Registry, Shot};
 use self::handle::Handle;
 use self::{generate::Generate, hit::Hit, shift::Move};
 use crate::direction::Direction;
 use crate::{
-    boss::Boss,
-    power::{Effect, PowerUp},
-    shield::Shield,
-    shooter::Shooter,
+    achievement::Achievement,
+    difficulty::Difficulty,
+    elapsed,
+    entity::{Entities, Entity, Id, Kind, Team},
+    error::{Error, Result},
+    event::{Cause, Event, Weapon},
+    flash::{Burst, Flash},
+    garbage::Garbage,
+    object::Object,
+    power::Effect,
+    role::Role,
+    sprite::Sprite,
+    util,
 };
-use ncurses::{getmaxx, getmaxy, WINDOW};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+use serde::{Deserialize, Serialize};
 use std::{
-    collections::HashMap,
-    time::{Duration, Instant},
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::Duration,
 };
 
-const BOSS_SCORE: i32 = 4000;
-const ENEMY_SCORE: i32 = 20;
+/// Points for shooting down the boss, times the level.
+pub const BOSS_SCORE: i32 = 4000;
+/// Points for each enemy killed, times the level.
+pub const ENEMY_SCORE: i32 = 20;
 const ENEMY_ROWS: i32 = 5;
 const ENEMIES_PER_ROW: i32 = 10;
-const POWER_COOLDOWN: Duration = Duration::from_secs(10);
+/// The fewest rows a board takes, so that the first wave fits above the shields.
+pub const MIN_HEIGHT: i32 = 2 * (ENEMY_ROWS + 1) + 4;
+/// The fewest columns a board takes, so that the first wave has room to move.
+pub const MIN_WIDTH: i32 = 2 * ENEMIES_PER_ROW + 2;
+/// How long most effects last once collected.
+pub const POWER_COOLDOWN: Duration = Duration::from_secs(10);
 const ATTACK_COOLDOWN: Duration = Duration::from_millis(600);
 const DOUBLE_ATTACK_COOLDOWN: Duration = Duration::from_millis(1000);
 const TRIPLE_ATTACK_COOLDOWN: Duration = Duration::from_millis(1600);
-const COMBINED_ATTACK_COOLDOWN: Duration = Duration::from_millis(2000);
-const POWER_PROBABILITY: f32 = 0.08;
-const FIRE_PROBABILITY: f32 = 0.05;
 const BOSS_PROPABILITY: f32 = 0.001;
 const SHIELDS: i32 = 13;
 const OBSTACLES: i32 = 4;
-const YIELDS: i32 = 4;
-const CHAR_PLAYER: u32 = '*' as u32;
-const CHAR_ENEMY: u32 = 'v' as u32;
-const CHAR_LASER: u32 = ':' as u32;
-const CHAR_BULLET: u32 = '.' as u32;
-const CHAR_OBSTACLE: u32 = ' ' as u32;
-const CHAR_SHIELD: u32 = '_' as u32;
-const CHAR_FOLLOWER: u32 = CHAR_SHIELD;
-pub const CHAR_BOSS: u32 = 'V' as u32;
-const CHAR_ULTRA: u32 = ' ' as u32;
-pub const COLOR_LASER: i16 = 1;
-const COLOR_ENEMY: i16 = 2;
-const COLOR_PLAYER: i16 = 3;
-const COLOR_FOLLOWER: i16 = COLOR_PLAYER;
-const COLOR_BULLET: i16 = 4;
-const COLOR_ALLY: i16 = COLOR_BULLET;
-pub const COLOR_POWERS: i16 = 5;
-pub const COLOR_SHIELDS: i16 = 6;
-pub const COLOR_OBSTACLES: i16 = 7;
-pub const COLOR_BOSS: i16 = 8;
-const COLOR_ULTRA: i16 = 9;
+/// How many moves the formation backs away for under [`Effect::Yield`].
+pub const YIELDS: i32 = 4;
+// Kills this close together add up to a combo
+const COMBO_WINDOW: Duration = Duration::from_millis(1500);
 
+/// The whole state of a game, and the rules that move it along.
+///
+/// Serializing it is how runs are saved. Time only passes through [`Logic::advance`], so
+/// timers hold how long ago they started in game time rather than by the clock.
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Logic {
-    enemies: Vec<Shooter>,
-    player: Shooter,
-    powers: Vec<PowerUp>,
-    shields: Vec<Shield>,
-    obstacles: Vec<Shield>,
-    follower: Option<Shield>,
-    effects: HashMap<Effect, Instant>,
-    boss: Option<Boss>,
+    entities: Entities,
+    // The player is spawned first and never leaves the board
+    player: Id,
+    #[serde(with = "elapsed::map")]
+    effects: HashMap<Effect, Duration>,
     height: i32,
     width: i32,
+    #[serde(default)]
+    difficulty: Difficulty,
     dir: Direction,
-    last_attack: Instant,
+    #[serde(with = "elapsed")]
+    last_attack: Duration,
     last_dir: Option<Direction>,
     yield_counter: i32,
     cooldown_attack: Duration,
-    score_increment: i32,
-    xerox: Option<Shooter>,
     slow_down: bool,
+    shots: i32,
+    plain_shots: i32,
+    #[serde(skip)]
+    feats: Vec<Achievement>,
+    #[serde(with = "elapsed::option")]
+    slowdown: Option<Duration>,
+    #[serde(skip)]
+    flashes: Vec<Flash>,
+    #[serde(skip)]
+    events: Vec<Event>,
+    // Effects that were active on the last update, to tell when they run out
+    #[serde(default)]
+    active: HashSet<Effect>,
+    combo: i32,
+    #[serde(with = "elapsed::option")]
+    last_kill: Option<Duration>,
+    // Every roll of the dice, so that a seed replays the same game. It is the generator
+    // behind StdRng, which can't be saved, so that resumed runs keep rolling the same
+    rng: ChaCha12Rng,
+    // What each effect does, which isn't saved along with the game
+    #[serde(skip)]
+    registry: Arc<Registry>,
 }
 
 impl Logic {
-    pub fn new(win: WINDOW) -> Self {
-        let y = getmaxy(win);
-        let x = getmaxx(win);
-        Self {
-            enemies: vec![],
-            powers: vec![],
+    /// An empty board of `y` rows and `x` columns, with only the player on it, unless
+    /// it is smaller than [`MIN_HEIGHT`] by [`MIN_WIDTH`].
+    pub fn new(y: i32, x: i32) -> Result<Self> {
+        if y < MIN_HEIGHT || x < MIN_WIDTH {
+            return Err(Error::Board { rows: y, cols: x });
+        }
+        let mut entities = Entities::default();
+        let player = entities.spawn(Entity::new(
+            Kind::Player,
+            (y - 2, x / 2),
+            Sprite::Player,
+            Role::Player,
+            Team::Player,
+        ));
+        Ok(Self {
+            entities,
+            player,
             effects: HashMap::new(),
-            shields: vec![],
-            obstacles: vec![],
-            player: Shooter::new((y - 2, x / 2), CHAR_PLAYER, COLOR_PLAYER),
-            follower: None,
-            boss: None,
-            xerox: None,
             height: y,
             width: x,
+            difficulty: Difficulty::Normal,
             dir: Direction::Right,
-            last_attack: Instant::now(),
+            last_attack: Duration::ZERO,
             last_dir: None,
             yield_counter: 0,
             cooldown_attack: ATTACK_COOLDOWN,
-            score_increment: 0,
             slow_down: false,
+            shots: 0,
+            plain_shots: 0,
+            feats: vec![],
+            slowdown: None,
+            flashes: vec![],
+            events: vec![],
+            active: HashSet::new(),
+            combo: 0,
+            last_kill: None,
+            rng: ChaCha12Rng::from_entropy(),
+            registry: Arc::default(),
+        })
+    }
+
+    /// Rolls the dice from `seed`, so that the same inputs at the same times play out
+    /// the same game every time.
+    pub fn with_seed(self, seed: u64) -> Self {
+        Self {
+            rng: ChaCha12Rng::seed_from_u64(seed),
+            ..self
         }
     }
 
+    /// Sets how hard the game is.
+    pub fn with_difficulty(self, difficulty: Difficulty) -> Self {
+        Self { difficulty, ..self }
+    }
+
+    /// Puts `handler` in charge of `effect` for this game, instead of its built-in
+    /// behavior. See [`effects`] for when each hook is called.
+    ///
+    /// Other effects still look at whether Jump is going, whoever handles it: Ultra and
+    /// Kamizake head down the board while it is, and so do the bullets fired from
+    /// grenades and mind controlled enemies.
+    pub fn with_handler(mut self, effect: Effect, handler: impl EffectHandler + 'static) -> Self {
+        Arc::make_mut(&mut self.registry).register(effect, handler);
+        self
+    }
+
+    /// Spawns the next wave and bumps `level`, rewarding the wave just cleared.
     pub fn level_up(&mut self, level: &mut i32) {
+        // The very first wave is generated, not cleared
+        if *level > 0 {
+            self.events.push(Event::WaveCleared(*level));
+            if self.shots == 0 {
+                self.feats.push(Achievement::Pacifist);
+            } else if self.plain_shots == 0 {
+                self.feats.push(Achievement::Needlework);
+            }
+        }
+        self.shots = 0;
+        self.plain_shots = 0;
         Generate::enemies(self);
         *level += 1;
     }
 
+    /// Fires with every active effect applied, unless the gun is still reloading.
     pub fn player_shoot(&mut self) {
-        self.player
-            .set_revert(Handle::power(&self.effects, &Effect::Jump));
-        let cooldown = if Handle::power(&self.effects, &Effect::Quickshot) {
-            self.cooldown_attack / 2
-        } else {
-            self.cooldown_attack
+        let mut shot = Shot {
+            reload: self.cooldown_attack,
+            cooldown: ATTACK_COOLDOWN,
+            bullets: vec![(self.player().pos(), Direction::Up)],
+            explosive: false,
+            reversed: false,
+            plain: true,
         };
-        if self.last_attack.elapsed() >= cooldown {
-            self.player.shoot(
-                Direction::Up,
-                Handle::power(&self.effects, &Effect::Grenade),
-                CHAR_BULLET,
-                COLOR_BULLET,
-            );
-            Handle::attack(self);
-            self.last_attack = Instant::now();
+        for handler in self.handlers() {
+            handler.modify_shot(self, &mut shot);
+        }
+        if self.slowdown.is_some_and(|time| time < POWER_COOLDOWN) {
+            shot.reload *= 2;
+        }
+        if self.last_attack >= shot.reload {
+            for (i, (pos, dir)) in shot.bullets.into_iter().enumerate() {
+                let dir = if shot.reversed { dir.opposite() } else { dir };
+                self.fire(pos, dir, shot.explosive, Sprite::Bullet, Role::Bullet);
+                // The xerox fires along with the player's own gun. It doesn't jump along
+                // with the player, so it keeps firing upwards
+                if i == 0 {
+                    if let Some(xerox) = self.entities.first(Kind::Xerox) {
+                        let pos = xerox.pos();
+                        self.fire(pos, Direction::Up, false, Sprite::Bullet, Role::Bullet);
+                    }
+                }
+            }
+            self.events.push(Event::PlayerShot);
+            self.cooldown_attack = shot.cooldown;
+            self.last_attack = Duration::ZERO;
+            self.shots += 1;
+            if shot.plain {
+                self.plain_shots += 1;
+            }
         }
     }
 
+    /// Moves the player, dragging the xerox and the follower along.
     pub fn player_move(&mut self, direction: &Direction) {
         Move::player(self, direction);
         Move::xerox(self);
         Move::follower(self);
     }
 
+    /// Lets enemies fire and spawns whatever the active effects and luck call for.
     pub fn generate(&mut self) {
         Generate::enemy_attack(self);
         Generate::power(self);
         Generate::boss(self);
-        Generate::xerox(self);
-        Generate::obstacles(self);
-        Generate::shields(self);
-        Generate::follower(self);
+        for handler in self.handlers() {
+            handler.on_generate(self);
+        }
     }
 
-    pub fn shift(&mut self, level: &i32) -> bool {
+    /// Moves bullets and foes.
+    pub fn shift(&mut self) {
         Move::bullets(self);
-        // When both bullets and targets move, they might collide
-        // But such collision is skipped if we don't check after
-        // the bullets' movements
-        Hit::targets(self, level);
-        Move::foes(self)
+        Move::foes(self);
     }
 
-    pub fn handle(&mut self) -> bool {
-        Handle::jump(self);
-        Handle::mind_control(self);
-        Handle::kamizake(self)
+    /// Applies and expires ongoing effects.
+    pub fn handle(&mut self) {
+        Handle::expire(self);
+        for handler in self.handlers() {
+            handler.on_tick(self);
+        }
     }
 
-    pub fn hit(&mut self, level: &i32) -> bool {
+    /// Resolves collisions between everything whose paths crossed since the last
+    /// call, so that nothing tunnels through anything else however they moved.
+    pub fn hit(&mut self) {
         Hit::powers(self);
-        Hit::shields(self);
-        Hit::follower(self);
-        Hit::targets(self, level);
-        Hit::obstacles(self);
-        Hit::player(self)
+        for handler in self.handlers() {
+            handler.on_lasers(self);
+        }
+        Hit::targets(self);
+        for handler in self.handlers() {
+            handler.on_enemy_contact(self);
+        }
+        if Hit::player(self) {
+            self.wreck(Cause::Laser);
+        }
+        self.entities.settle();
+    }
+
+    fn handler(&self, effect: Effect) -> Arc<dyn EffectHandler> {
+        self.registry.get(effect)
+    }
+
+    // The handlers of the effects going on, always in the same order
+    fn handlers(&self) -> Vec<Arc<dyn EffectHandler>> {
+        self.registry.going(&self.effects)
+    }
+
+    // Jump turns the player's shots around
+    fn aim(&self, dir: Direction) -> Direction {
+        if Handle::power(&self.effects, &Effect::Jump) {
+            dir.opposite()
+        } else {
+            dir
+        }
+    }
+
+    // Shots on the player's side belong to the player, unless a mind controlled enemy
+    // fired them
+    fn fire(
+        &mut self,
+        pos: (i32, i32),
+        dir: Direction,
+        is_explosive: bool,
+        sprite: Sprite,
+        color: Role,
+    ) {
+        self.fire_as(self.player, pos, dir, is_explosive, sprite, color);
+    }
+
+    // A bullet doesn't run into whoever fired it, even though they start on the same cell
+    fn fire_as(
+        &mut self,
+        owner: Id,
+        pos: (i32, i32),
+        dir: Direction,
+        is_explosive: bool,
+        sprite: Sprite,
+        color: Role,
+    ) {
+        let bullet = Entity::new(Kind::Bullet, pos, sprite, color, Team::Player)
+            .with_velocity(dir)
+            .with_owner(owner)
+            .with_explosive(is_explosive);
+        self.entities.spawn(bullet);
+    }
+
+    fn player_mut(&mut self) -> &mut Entity {
+        self.entities
+            .get_mut(self.player)
+            .expect("The player is never despawned")
+    }
+
+    // The column across the board from the player, where the xerox goes
+    fn mirror(&self) -> i32 {
+        let player = self.player();
+        self.width - 1 - (player.pos().1 + player.extent().1 - 1)
     }
 
-    pub fn enemies(&self) -> &[Shooter] {
-        self.enemies.as_ref()
+    fn wreck(&mut self, cause: Cause) {
+        self.events.push(Event::PlayerHit { cause });
+        let flashes = Flash::area(&self.player().pos(), 1, self.size(), Burst::Wreck);
+        self.flashes.extend(flashes);
     }
 
-    pub fn player(&self) -> &Shooter {
-        &self.player
+    // Explodes the enemies among the entities taken off the board, blaming the weapon for it
+    fn debris(&mut self, gone: &[Entity], by: Weapon) {
+        for enemy in gone {
+            if enemy.kind() == Kind::Enemy {
+                let flashes = Flash::cover(enemy, self.size(), Burst::Explosion);
+                self.flashes.extend(flashes);
+                self.events.push(Event::EnemyKilled {
+                    id: enemy.id(),
+                    pos: enemy.pos(),
+                    by,
+                });
+            }
+        }
+    }
+
+    /// Ages explosions and drops the ones that are over.
+    pub fn fade(&mut self) {
+        for flash in self.flashes.iter_mut() {
+            flash.age();
+        }
+        self.flashes.retain(|flash| !flash.is_over());
+    }
+
+    /// Lets `elapsed` of game time go by, bringing effects and reloads closer to
+    /// running out. Nothing else moves the clock, so a paused game stands still.
+    pub fn advance(&mut self, elapsed: Duration) {
+        for time in self.effects.values_mut() {
+            *time += elapsed;
+        }
+        self.last_attack += elapsed;
+        for time in [&mut self.slowdown, &mut self.last_kill]
+            .into_iter()
+            .flatten()
+        {
+            *time += elapsed;
+        }
+    }
+
+    /// Applies `effect` as if the player had just shot its power-up, except that
+    /// Explode goes off around the player.
+    pub fn grant(&mut self, effect: Effect) {
+        let pos = self.player().pos();
+        Handle::apply(self, effect, &pos);
+    }
+
+    /// Rolls the game's own dice, so that whatever hangs on the outcome plays out the
+    /// same again from the same seed.
+    pub fn roll(&mut self, odds: f32) -> bool {
+        util::random_event(&mut self.rng, odds)
+    }
+
+    /// Drops a power-up of [`Kind::Custom`] anywhere on the board, shown as `glyph`.
+    /// Shooting it only reports [`Event::CustomCollected`], what it does is up to the caller.
+    pub fn drop_custom(&mut self, custom: u16, glyph: char) {
+        let pos = Generate::spot(self);
+        Generate::pickup(self, pos, Kind::Custom(custom), glyph);
+    }
+
+    /// Fires one of the player's bullets from `pos` towards `dir`, with no effect applied
+    /// and regardless of the reload. Positions off the board are pulled back onto its
+    /// nearest cell.
+    pub fn shoot_from(&mut self, pos: (i32, i32), dir: Direction) {
+        let pos = (
+            pos.0.clamp(2, self.height - 2),
+            pos.1.clamp(1, self.width - 2),
+        );
+        self.fire(pos, dir, false, Sprite::Bullet, Role::Bullet);
+    }
+
+    /// Steps every enemy that isn't numb one cell towards `dir`, as long as it stays on
+    /// the board.
+    pub fn move_enemies(&mut self, dir: Direction) {
+        let size = self.size();
+        for enemy in self.entities.of_mut(Kind::Enemy) {
+            if !enemy.is_numb() {
+                enemy.shift(&dir, size);
+            }
+        }
+    }
+
+    /// Picks the garbage to send a versus opponent, rolling the game's own dice.
+    pub fn garbage(&mut self) -> Garbage {
+        self.rng.gen()
+    }
+
+    /// Applies garbage sent by a versus opponent.
+    pub fn receive(&mut self, garbage: &Garbage) {
+        match garbage {
+            Garbage::Enemies => Generate::reinforcements(self),
+            Garbage::Jump => {
+                self.effects.insert(Effect::Jump, Duration::ZERO);
+            }
+            Garbage::Slowdown => self.slowdown = Some(Duration::ZERO),
+        }
+    }
+
+    /// Rows and columns of the board, borders included.
+    pub fn size(&self) -> (i32, i32) {
+        (self.height, self.width)
     }
 
-    pub fn powers(&self) -> &[PowerUp] {
-        self.powers.as_ref()
+    /// Whether `pos` is inside the border, where entities are allowed to be.
+    pub fn on_board(&self, pos: (i32, i32)) -> bool {
+        !util::out_of_bounds(pos, self.size())
     }
 
-    pub fn shields(&self) -> &[Shield] {
-        self.shields.as_ref()
+    /// How hard the game is.
+    pub fn difficulty(&self) -> Difficulty {
+        self.difficulty
     }
 
-    pub fn boss(&self) -> Option<&Boss> {
-        self.boss.as_ref()
+    /// Everything on the board, oldest first.
+    pub fn entities(&self) -> &Entities {
+        &self.entities
     }
 
-    pub fn active_effects(&self) -> Vec<Effect> {
-        let mut effects: Vec<Effect> = vec![];
+    /// The player's ship, which is always on the board.
+    pub fn player(&self) -> &Entity {
+        self.entities
+            .get(self.player)
+            .expect("The player is never despawned")
+    }
+
+    /// The formation, mind controlled enemies included.
+    pub fn enemies(&self) -> impl Iterator<Item = &Entity> {
+        self.entities.of(Kind::Enemy)
+    }
+
+    /// The boss, while it is crossing the board.
+    pub fn boss(&self) -> Option<&Entity> {
+        self.entities.first(Kind::Boss)
+    }
+
+    /// The time left on each active effect, the closest to running out first.
+    pub fn effect_timers(&self) -> Vec<(Effect, Duration)> {
+        let mut timers: Vec<(Effect, Duration)> = vec![];
         for (effect, time) in &self.effects {
-            if time.elapsed() < POWER_COOLDOWN {
-                effects.push(*effect);
+            // An effect is over once its time is up, not a tick later
+            if Handle::power(&self.effects, effect) {
+                timers.push((*effect, POWER_COOLDOWN - *time));
             }
         }
-        effects
+        timers.sort_by_key(|(effect, remaining)| (*remaining, effect.to_string()));
+        timers
+    }
+
+    /// How many kills in a row are still adding up, or zero once the window closed.
+    pub fn combo(&self) -> i32 {
+        match self.last_kill {
+            Some(time) if time < COMBO_WINDOW => self.combo,
+            _ => 0,
+        }
     }
 
-    pub fn follower(&self) -> Option<&Shield> {
-        self.follower.as_ref()
+    /// How much of its crossing the boss has left before it escapes, from 1 down to 0.
+    pub fn boss_escape(&self) -> Option<f32> {
+        self.boss().map(|boss| {
+            let crossing = self.width - 2 - boss.extent().1;
+            let left = self.width - 1 - boss.extent().1 - boss.pos().1;
+            left as f32 / crossing as f32
+        })
     }
 
-    pub fn score_increment(&self) -> i32 {
-        self.score_increment
+    /// Achievements earned since the last [`Logic::feats_reset`].
+    pub fn feats(&self) -> &[Achievement] {
+        self.feats.as_ref()
     }
 
-    pub fn score_reset(&mut self) {
-        self.score_increment = 0;
+    /// Forgets the achievements read back so far.
+    pub fn feats_reset(&mut self) {
+        self.feats.clear();
     }
 
-    pub fn enemies_mut(&mut self) -> &mut Vec<Shooter> {
-        &mut self.enemies
+    /// What happened since the last [`Logic::events_reset`].
+    pub fn events(&self) -> &[Event] {
+        self.events.as_ref()
     }
 
-    pub fn xerox(&self) -> Option<&Shooter> {
-        self.xerox.as_ref()
+    /// Forgets the events read back so far, once per tick.
+    pub fn events_reset(&mut self) {
+        self.events.clear();
     }
 
-    pub fn obstacles(&self) -> &[Shield] {
-        self.obstacles.as_ref()
+    /// Explosions and beams, which are only there to be drawn.
+    pub fn flashes(&self) -> &[Flash] {
+        self.flashes.as_ref()
     }
 }
@@ -1,138 +1,140 @@
-use super::{handle::Handle, Logic};
+use super::{effects::March, handle::Handle, Logic};
 use crate::object::Object;
-use crate::{direction::Direction, power::Effect, shooter::Shooter, util};
+use crate::{
+    direction::Direction,
+    entity::{Kind, Team},
+    event::Event,
+    util,
+};
 
 pub struct Move;
 
 impl Move {
     pub fn boss(logic: &mut Logic) {
-        if let Some(boss) = logic.boss.as_mut() {
-            boss.shift();
-            if boss.left_pos() == logic.width - 2 {
-                logic.boss = None;
-            }
+        let width = logic.width;
+        let mut escaped = false;
+        for boss in logic.entities.of_mut(Kind::Boss) {
+            boss.advance();
+            escaped |= boss.pos().1 + boss.extent().1 - 1 == width - 2;
+        }
+        if escaped {
+            logic.entities.retain(|entity| entity.kind() != Kind::Boss);
+            logic.events.push(Event::BossEscaped);
         }
     }
 
     pub fn player(logic: &mut Logic, direction: &Direction) {
-        let new_pos = logic.player.new_pos(direction);
-        let warp = Handle::power(&logic.effects, &Effect::Warp) && util::out_of_bounds(new_pos);
-
-        if *direction == Direction::Left && warp {
-            logic.player.set_y(logic.width - 2);
-        } else if *direction == Direction::Right && warp {
-            logic.player.set_y(1);
-        } else {
-            logic.player.shift(direction);
+        let mut moved = false;
+        for handler in logic.handlers() {
+            moved = moved || handler.on_player_move(logic, direction);
+        }
+        if !moved {
+            let size = logic.size();
+            logic.player_mut().shift(direction, size);
         }
     }
 
     pub fn xerox(logic: &mut Logic) {
-        if let Some(xerox) = &mut logic.xerox {
-            let player_pos = logic.player.pos();
-            let y = logic.width - player_pos.1;
-            xerox.set_y(y);
+        let y = logic.mirror();
+        for xerox in logic.entities.of_mut(Kind::Xerox) {
+            xerox.set_col(y);
         }
     }
 
     pub fn follower(logic: &mut Logic) {
         let player_x = logic.player().pos().1;
-        if let Some(follower) = logic.follower.as_mut() {
-            follower.set_x(player_x);
+        for follower in logic.entities.of_mut(Kind::Follower) {
+            follower.set_col(player_x);
         }
     }
 
-    pub fn foes(logic: &mut Logic) -> bool {
-        if Handle::power(&logic.effects, &Effect::Zombify) {
-            logic.slow_down = !logic.slow_down;
-        } else {
-            logic.slow_down = false;
+    pub fn foes(logic: &mut Logic) {
+        let mut march = March::default();
+        for handler in logic.handlers() {
+            handler.modify_enemy_move(logic, &mut march);
         }
-        if !logic.slow_down {
+        if march.foes {
             Move::lasers(logic);
             Move::boss(logic);
-            return Move::enemies(logic);
+            Move::enemies(logic, &march);
         }
-        false
     }
 
     pub fn lasers(logic: &mut Logic) {
-        for enemy in logic.enemies.iter_mut() {
-            for bullet in enemy.bullets_mut() {
-                bullet.shift();
+        for laser in logic.entities.of_mut(Kind::Bullet) {
+            if laser.team() == Team::Enemy {
+                laser.advance();
             }
-            enemy.clear_bullets();
         }
+        Move::clear(logic, Team::Enemy);
     }
 
     pub fn bullets(logic: &mut Logic) {
-        let reflect = Handle::power(&logic.effects, &Effect::Reflect);
-        for bullet in logic.player.bullets_mut() {
-            bullet.shift();
-            if util::out_of_bounds(bullet.pos()) && reflect {
-                let new_dir = match bullet.dir() {
-                    Direction::Up => Direction::Down,
-                    Direction::Down => Direction::Up,
-                    Direction::LeftUp => Direction::RightUp,
-                    Direction::RightUp => Direction::LeftUp,
-                    _ => Direction::Up,
-                };
-                bullet.set_dir(new_dir);
-                bullet.shift();
+        let size = logic.size();
+        let handlers = logic.handlers();
+        for bullet in logic.entities.of_mut(Kind::Bullet) {
+            if bullet.team() != Team::Player {
+                continue;
+            }
+            bullet.advance();
+            if util::out_of_bounds(bullet.pos(), size) {
+                for handler in &handlers {
+                    handler.on_bullet_out(bullet);
+                }
             }
         }
-        logic.player.clear_bullets();
+        Move::clear(logic, Team::Player);
+    }
+
+    // Drops the side's bullets that have left the board
+    fn clear(logic: &mut Logic, team: Team) {
+        let size = logic.size();
+        logic.entities.retain(|entity| {
+            entity.kind() != Kind::Bullet
+                || entity.team() != team
+                || !util::out_of_bounds(entity.pos(), size)
+        });
     }
 
-    pub fn enemies(logic: &mut Logic) -> bool {
-        if !Handle::power(&logic.effects, &Effect::Lock) {
-            let (left, right) = get_outermost_lateral_indexes(&logic.enemies);
+    pub fn enemies(logic: &mut Logic, march: &March) {
+        if march.formation {
+            let right_edge = logic
+                .enemies()
+                .map(|enemy| enemy.pos().1 + enemy.extent().1 - 1)
+                .max();
+            let left_edge = logic.enemies().map(|enemy| enemy.pos().1).min();
 
-            if logic.enemies.is_empty() {
-                logic.dir = Direction::Left;
-            } else if logic.yield_counter > 0 {
-                Handle::yields(logic);
-            } else if logic.dir == Direction::Right
-                && logic.enemies[right].pos().1 == logic.width - 2
-                || logic.enemies[left].pos().1 == 1 && logic.dir == Direction::Left
-            {
-                logic.dir = Direction::Down;
-            } else if logic.dir == Direction::Down
-                && logic.enemies[right].pos().1 == logic.width - 2
-            {
+            if let (Some(left), Some(right)) = (left_edge, right_edge) {
+                if logic.yield_counter > 0 {
+                    Handle::yields(logic);
+                } else if logic.dir == Direction::Right && right == logic.width - 2
+                    || left == 1 && logic.dir == Direction::Left
+                {
+                    logic.dir = Direction::Down;
+                } else if logic.dir == Direction::Down && right == logic.width - 2 {
+                    logic.dir = Direction::Left;
+                } else if logic.dir == Direction::Down && left == 1 {
+                    logic.dir = Direction::Right;
+                }
+            } else {
                 logic.dir = Direction::Left;
-            } else if logic.dir == Direction::Down && logic.enemies[left].pos().1 == 1 {
-                logic.dir = Direction::Right;
             }
 
-            if !(logic.dir == Direction::Down)
-                || !Handle::power(&logic.effects, &Effect::Antigravity)
-            {
-                for enemy in logic.enemies.iter_mut() {
+            if logic.dir != Direction::Down || march.descend {
+                let (dir, size) = (logic.dir, logic.size());
+                for enemy in logic.entities.of_mut(Kind::Enemy) {
                     if !enemy.is_numb() {
-                        enemy.shift(&logic.dir);
+                        enemy.shift(&dir, size);
                     }
                 }
             }
         }
 
-        if let Some(bottom) = logic.enemies.last() {
-            bottom.pos().0 == logic.height - 2
-        } else {
-            false
-        }
-    }
-}
-
-fn get_outermost_lateral_indexes(shooters: &[Shooter]) -> (usize, usize) {
-    let mut left_index = 0;
-    let mut right_index = 0;
-    for i in 1..shooters.len() {
-        if shooters[i].pos().1 <= shooters[left_index].pos().1 {
-            left_index = i;
-        } else if shooters[i].pos().1 >= shooters[right_index].pos().1 {
-            right_index = i;
+        let landed = logic
+            .enemies()
+            .any(|enemy| enemy.pos().0 + enemy.extent().0 - 1 == logic.height - 2);
+        if landed {
+            logic.events.push(Event::FormationLanded);
         }
     }
-    (left_index, right_index)
 }
@@ -1,26 +1,45 @@
-mod boss;
-mod bullet;
-mod direction;
+mod achievements;
+mod canvas;
+mod cli;
+mod controller;
 mod game;
-mod logic;
-mod object;
-mod power;
+mod headless;
+mod help;
+mod hud;
+mod journal;
+mod keymap;
 mod printer;
-mod shield;
-mod shooter;
-mod util;
+mod render;
+mod replay;
+mod save;
+mod session;
+mod spectate;
+mod theme;
+mod versus;
 mod window;
+use cli::{Mode, Options, USAGE};
+use controller::{Autopilot, Keyboard, PlayerController};
+use keymap::Keymap;
 use ncurses::*;
+use replay::{Playback, Replay, Setup};
+use session::Session;
+use space_invaders::{script::Scripts, Error};
+use spectate::{Broadcast, Spectator};
+use std::{env, panic, process};
+use theme::Theme;
+use versus::Versus;
 
 fn initialize() {
+    setlocale(LcCategory::all, "");
     initscr();
     cbreak();
     noecho();
     curs_set(CURSOR_VISIBILITY::CURSOR_INVISIBLE);
     refresh();
-    assert!(has_colors());
-    start_color();
-    use_default_colors();
+    if has_colors() {
+        start_color();
+        use_default_colors();
+    }
 }
 
 fn finalize() {
@@ -28,32 +47,185 @@ fn finalize() {
     endwin();
 }
 
-fn colors() {
-    const RAINBOW_COLORS: i16 = 7;
-    init_pair(RAINBOW_COLORS, 7, 7);
-    init_pair(RAINBOW_COLORS + 1, 0, -1);
-    init_pair(RAINBOW_COLORS + 2, 4, 4);
-    for i in 1..RAINBOW_COLORS {
-        init_pair(i, i, -1);
-    }
+// Puts the terminal back before reporting a panic, or it is left raw and silent
+fn restore_on_panic() {
+    let report = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        endwin();
+        report(info);
+    }));
 }
 
-pub const LINES: i32 = 24;
-pub const COLS: i32 = 40;
+fn versus(options: &Options) -> Result<Option<Versus>, Error> {
+    let versus = match &options.mode {
+        Mode::Host(port) => {
+            println!("Waiting for an opponent on port {port}...");
+            Versus::host(*port)
+        }
+        Mode::Join(addr) => Versus::join(addr.as_str()),
+        _ => return Ok(None),
+    };
+    versus
+        .map(Some)
+        .map_err(Error::network("Couldn't connect to the opponent"))
+}
+
+fn broadcast(options: &Options) -> Result<Option<Broadcast>, Error> {
+    let Some(port) = options.broadcast else {
+        return Ok(None);
+    };
+    Broadcast::new(port)
+        .map(Some)
+        .map_err(Error::network(format!("Couldn't broadcast on port {port}")))
+}
+
+fn spectate(addr: &str, theme: Theme, monochrome: bool) -> Result<(), Error> {
+    let mut spectator = Spectator::connect(addr)
+        .map_err(Error::network(format!("Couldn't reach the game at {addr}")))?;
 
-fn main() {
     initialize();
-    colors();
+    let result = theme.install(monochrome).and_then(|_| spectator.watch());
 
-    let game_window: WINDOW = window::get_centralized_window(LINES, COLS);
-    let mut invaders = game::Invaders::new(game_window);
-    invaders.init();
+    drop(spectator);
+    finalize();
+
+    match result {
+        Err(Error::Io(source)) => Err(Error::network(format!("Lost the game at {addr}"))(source)),
+        result => result,
+    }
+}
+
+// Whether the player chose to play rather than quit
+fn title(keymap: &Keymap) -> bool {
+    loop {
+        match printer::Printer::menu("INVADERS", &["Play", "Help", "Quit"]) {
+            0 => return true,
+            1 => printer::Printer::help(keymap),
+            _ => return false,
+        }
+    }
+}
 
-    const DELAY: i32 = 250;
-    wtimeout(game_window, DELAY);
+// The recording of the run, if asked for one
+fn play(
+    options: &Options,
+    setup: Setup,
+    scripts: Scripts,
+    keymap: Keymap,
+    replay: Option<Replay>,
+    versus: Option<Versus>,
+    broadcast: Option<Broadcast>,
+) -> Result<Option<Replay>, Error> {
+    window::fits(setup.size.0, setup.size.1)?;
+
+    // Matches, the autopilot and replays jump straight into the game
+    let bot = options.mode == Mode::Bot;
+    if versus.is_none() && !bot && replay.is_none() && !title(&keymap) {
+        return Ok(None);
+    }
+
+    // A saved run would play out differently from what was asked for
+    let fresh = options.custom || options.record.is_some() || replay.is_some();
+    let snapshot = if versus.is_none()
+        && !fresh
+        && save::exists()
+        && printer::Printer::confirm("Resume saved run?")
+    {
+        save::restore()
+    } else {
+        None
+    };
+    let session = match snapshot {
+        Some(snapshot) => {
+            let mut session = Session::resume(snapshot);
+            session.scripts = scripts;
+            session
+        }
+        None => setup.start(scripts)?,
+    };
+
+    let (rows, cols) = session.gate.size();
+    window::fits(rows, cols)?;
+    let game_window: WINDOW = window::get_centralized_window(rows, cols);
+    let keyboard = Keyboard::new(game_window, keymap.clone());
+    let controller: Box<dyn PlayerController> = match replay {
+        Some(replay) => Box::new(Playback::new(Some(keyboard), replay)),
+        None if bot => Box::new(Autopilot::new(keyboard)),
+        None => Box::new(keyboard),
+    };
+
+    let mut invaders = game::Invaders::new(game_window, session, controller, keymap);
+    invaders.init();
+    if let Some(versus) = versus {
+        invaders.set_versus(versus);
+    }
+    if let Some(broadcast) = broadcast {
+        invaders.set_broadcast(broadcast);
+    }
+    if options.record.is_some() {
+        invaders.set_recording(Replay::new(setup));
+    }
 
     invaders.game_loop();
+    let recording = invaders.take_recording();
 
+    drop(invaders);
     delwin(game_window);
+    Ok(recording)
+}
+
+fn run(args: &[String]) -> Result<(), Error> {
+    let options = Options::parse(args)?;
+    if options.help {
+        print!("{USAGE}");
+        return Ok(());
+    }
+    if options.mode == Mode::Headless {
+        return headless::run(&options);
+    }
+
+    let theme = Theme::load(&options.theme)?;
+    if let Mode::Spectate(addr) = &options.mode {
+        return spectate(addr, theme, options.monochrome());
+    }
+
+    // Everything that could go wrong is read before the terminal is taken over
+    let keymap = match &options.keymap {
+        Some(path) => Keymap::load(path)?,
+        None => Keymap::default(),
+    };
+    let replay = options.replay.as_deref().map(Replay::load).transpose()?;
+    let setup = replay
+        .as_ref()
+        .map_or_else(|| Setup::new(&options), |replay| replay.setup.clone());
+    let scripts = setup.scripts()?;
+    let versus = versus(&options)?;
+    let broadcast = broadcast(&options)?;
+
+    initialize();
+    let result = theme
+        .install(options.monochrome())
+        .and_then(|_| play(&options, setup, scripts, keymap, replay, versus, broadcast));
     finalize();
+
+    match (result?, &options.record) {
+        (Some(recording), Some(path)) => recording.store(path),
+        _ => Ok(()),
+    }
+}
+
+fn main() {
+    restore_on_panic();
+    let args: Vec<String> = env::args().skip(1).collect();
+    if let Err(e) = run(&args) {
+        eprintln!("{e}");
+        match e {
+            Error::Theme { .. } => {
+                eprintln!("Built-in themes: {}", Theme::builtin_names().join(", "))
+            }
+            Error::Usage(_) => eprintln!("Try --help for the options"),
+            _ => {}
+        }
+        process::exit(1);
+    }
 }
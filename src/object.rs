@@ -1,5 +1,40 @@
+//! What everything drawn on the board has in common.
+
+use crate::{role::Role, sprite::Sprite};
+
+/// Anything that takes up room on the board.
+///
+/// Positions are `(row, col)` pairs of the top left corner, with the board border at
+/// row and column zero.
 pub trait Object {
+    /// Where the object's top left corner is.
     fn pos(&self) -> (i32, i32);
-    fn char(&self) -> u32;
-    fn color(&self) -> i16;
+    /// What the object looks like, which also sets its extent.
+    fn sprite(&self) -> Sprite;
+    /// The theme role the object is drawn with.
+    fn color(&self) -> Role;
+
+    /// How many `(rows, cols)` the object spans.
+    fn extent(&self) -> (i32, i32) {
+        self.sprite().extent()
+    }
+
+    /// Whether the cell at `pos` is part of the object.
+    fn covers(&self, pos: (i32, i32)) -> bool {
+        let (row, col) = self.pos();
+        let (rows, cols) = self.extent();
+        pos.0 >= row && pos.0 < row + rows && pos.1 >= col && pos.1 < col + cols
+    }
+
+    /// Whether the two objects share at least one cell.
+    fn overlaps<T: Object>(&self, other: &T) -> bool {
+        let (row, col) = self.pos();
+        let (rows, cols) = self.extent();
+        let (other_row, other_col) = other.pos();
+        let (other_rows, other_cols) = other.extent();
+        row < other_row + other_rows
+            && other_row < row + rows
+            && col < other_col + other_cols
+            && other_col < col + cols
+    }
 }
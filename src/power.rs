@@ -1,37 +1,68 @@
-use crate::{logic::COLOR_POWERS, object::Object};
+//! The power-ups the player can collect.
+
 use rand::{
     distributions::{Distribution, Standard},
     Rng,
 };
+use serde::{Deserialize, Serialize};
 use std::fmt;
 
-#[derive(Clone, Copy, Eq, Hash, PartialEq, Debug)]
+/// What a power-up does once collected. Most effects last for [`POWER_COOLDOWN`].
+///
+/// [`POWER_COOLDOWN`]: crate::logic::POWER_COOLDOWN
+#[derive(Clone, Copy, Eq, Hash, PartialEq, Debug, Serialize, Deserialize)]
 pub enum Effect {
+    /// The formation stops dropping down at the edges.
     Antigravity,
+    /// Bullets and lasers destroy each other when they meet.
     Block,
+    /// Wipes every laser off the board at once.
     Clear,
+    /// Every shot fires a second bullet behind the first, at a slower reload.
     Double,
+    /// Destroys every enemy around where the power-up was.
     Explode,
+    /// A block over the player takes one laser for them.
     Follower,
+    /// Bullets burst into a stray shot when they hit an enemy.
     Grenade,
+    /// Enemies stop firing.
     Hijack,
+    /// Lasers pass through the player.
     Invincible,
+    /// The player moves to the top of the board and fires downwards.
     Jump,
+    /// The player charges forward on their own, taking the wave or a ship with them.
     Kamizake,
+    /// The formation freezes in place.
     Lock,
+    /// Shot enemies switch sides until the effect runs out.
     Mindcontrol,
+    /// Shot enemies stop moving instead of dying.
     Numb,
+    /// A column of blocks rises in the middle of the board.
     Obstacle,
+    /// Bullets go through enemies.
     Pierce,
+    /// Halves the reload.
     Quickshot,
+    /// Bullets bounce off the edges of the board.
     Reflect,
+    /// A row of shields guards the bottom of the board.
     Shield,
+    /// Every shot also fires two diagonal bullets, at a slower reload.
     Triple,
+    /// Shots are a beam filling the whole column.
     Ultra,
+    /// Enemies whose lasers hit the player are destroyed.
     Vendetta,
+    /// Moving past one side of the board comes back in on the other.
     Warp,
+    /// A mirrored copy of the player fires along with them.
     Xerox,
+    /// The formation backs away upwards for a few moves.
     Yield,
+    /// Enemies and their lasers move at half speed.
     Zombify,
 }
 
@@ -41,6 +72,75 @@ impl fmt::Display for Effect {
     }
 }
 
+impl Effect {
+    /// Every effect, in alphabetical order.
+    pub const ALL: [Effect; 26] = [
+        Effect::Antigravity,
+        Effect::Block,
+        Effect::Clear,
+        Effect::Double,
+        Effect::Explode,
+        Effect::Follower,
+        Effect::Grenade,
+        Effect::Hijack,
+        Effect::Invincible,
+        Effect::Jump,
+        Effect::Kamizake,
+        Effect::Lock,
+        Effect::Mindcontrol,
+        Effect::Numb,
+        Effect::Obstacle,
+        Effect::Pierce,
+        Effect::Quickshot,
+        Effect::Reflect,
+        Effect::Shield,
+        Effect::Triple,
+        Effect::Ultra,
+        Effect::Vendetta,
+        Effect::Warp,
+        Effect::Xerox,
+        Effect::Yield,
+        Effect::Zombify,
+    ];
+
+    /// The letter a power-up granting the effect is drawn as.
+    pub fn initial(&self) -> char {
+        format!("{self:?}").chars().next().unwrap_or('?')
+    }
+
+    /// What the effect does, as the help screen explains it to the player.
+    pub fn description(&self) -> &'static str {
+        match self {
+            Effect::Antigravity => "The formation stops dropping down at the edges and only slides sideways.",
+            Effect::Block => "Your bullets and enemy lasers destroy each other when they meet.",
+            Effect::Clear => "Wipes every enemy laser off the board.",
+            Effect::Double => "Every shot fires a second bullet right behind the first, but shots take longer to reload. Stacks with Triple.",
+            Effect::Explode => "Destroys every enemy within two cells of where the power-up was.",
+            Effect::Follower => "A block follows your ship just above it, taking one laser for you before it breaks.",
+            Effect::Grenade => "Bullets that hit an enemy burst into a stray shot in a random direction. Needs bullets that stop, so it does nothing with Pierce.",
+            Effect::Hijack => "Enemies stop firing, mind-controlled ones included.",
+            Effect::Invincible => "Lasers pass through your ship harmlessly.",
+            Effect::Jump => "Your ship moves to the top of the board and fires downwards. Turns Kamizake and Ultra around too.",
+            Effect::Kamizake => "Your ship charges forward on its own. Ramming an enemy wipes out the whole wave, but both that and flying off the board cost a life.",
+            Effect::Lock => "The formation freezes in place, though it keeps firing.",
+            Effect::Mindcontrol => "Enemies you shoot switch sides and fire for you in random directions, until the effect runs out and they vanish.",
+            Effect::Numb => "Enemies you shoot stop moving instead of dying, and destroy each other when the formation runs into them.",
+            Effect::Obstacle => "A column of blocks rises in the middle of the board, destroying enemies that run into it.",
+            Effect::Pierce => "Bullets go through enemies and keep flying.",
+            Effect::Quickshot => "Halves the time between shots.",
+            Effect::Reflect => "Bullets bounce off the edges of the board instead of leaving it.",
+            Effect::Shield => "A row of shields guards the bottom of the board, each taking a few lasers.",
+            Effect::Triple => "Every shot also fires two diagonal bullets, but shots take longer to reload. Stacks with Double.",
+            Effect::Ultra => "Fires a beam filling the whole column in front of your ship.",
+            Effect::Vendetta => "Enemies whose lasers hit your ship are destroyed. You still take the hit without Invincible.",
+            Effect::Warp => "Moving past one side of the board takes you to the other.",
+            Effect::Xerox => "A mirrored copy of your ship on the other side of the board fires along with you.",
+            Effect::Yield => "The formation backs away upwards for a few moves.",
+            Effect::Zombify => "Enemies and their lasers move at half speed.",
+        }
+    }
+}
+
 impl Distribution<Effect> for Standard {
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Effect {
         match rng.gen_range(0..=25) {
@@ -73,45 +173,3 @@ impl Distribution<Effect> for Standard {
         }
     }
 }
-
-#[derive(Clone)]
-pub struct PowerUp {
-    pos: (i32, i32),
-    effect: Effect,
-    char: u32,
-    color: i16,
-}
-
-impl Object for PowerUp {
-    fn pos(&self) -> (i32, i32) {
-        self.pos
-    }
-    fn char(&self) -> u32 {
-        self.char
-    }
-    fn color(&self) -> i16 {
-        self.color
-    }
-}
-
-impl PowerUp {
-    pub fn new(pos: (i32, i32), effect: Effect) -> Self {
-        let first_char = effect.to_string().chars().next();
-        match first_char {
-            Some(char) => {
-                let char = char as u32;
-                Self {
-                    pos,
-                    effect,
-                    char,
-                    color: COLOR_POWERS,
-                }
-            }
-            None => panic!("Woopsie. Effect {effect} contains no characters. How did this happen?"),
-        }
-    }
-
-    pub fn effect(&self) -> &Effect {
-        &self.effect
-    }
-}
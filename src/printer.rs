@@ -1,23 +1,18 @@
-use crate::object::Object;
-use crate::{power::Effect, shooter::Shooter, window, COLS, LINES};
+use crate::{
+    canvas::{Canvas, Pen},
+    help,
+    keymap::Keymap,
+    theme, window,
+};
 use ncurses::*;
+use space_invaders::{object::Object, role::Role, sprite::Sprite};
 
 pub struct Printer;
 
 impl Printer {
-    pub fn clear(win: WINDOW) {
-        let x = getmaxx(win);
-        let y = getmaxy(win);
-
-        for i in 1..x - 1 {
-            for j in 1..y - 1 {
-                mvwaddch(win, j, i, ' ' as u32);
-            }
-        }
-    }
-
-    pub fn header(score: i32, win: WINDOW, lives: i8) {
-        mvwprintw(win, 1, 1, &format!("SCORE: {score}"));
+    pub fn header(score: i32, canvas: &mut Canvas, lives: i8) {
+        let pen = Pen::new(Role::Hud);
+        canvas.write((1, 1), &format!("SCORE: {score}"), pen);
 
         let mut live_str: String = "SHIPS: ".to_string();
         if lives > 0 {
@@ -27,29 +22,19 @@ impl Printer {
         } else {
             live_str += "LAST";
         }
-        let max_x = getmaxx(win);
+        let max_x = canvas.cols();
         let x = max_x - (live_str.len() as i32) - 1;
 
-        mvwprintw(win, 1, x, &live_str);
+        canvas.write((1, x), &live_str, pen);
     }
 
-    pub fn footer(effects: Vec<Effect>) {
-        const MAX_STR_SIZE: i32 = COLS - 15;
-        let window = window::get_mid_window(3, COLS, LINES + 13);
-        box_(window, 0, 0);
-        let mut effects_string = String::new();
-        for effect in effects {
-            effects_string += &format!("{effect},");
-        }
-        if !effects_string.is_empty() {
-            effects_string.pop();
-        }
-        if effects_string.len() as i32 >= MAX_STR_SIZE {
-            effects_string = effects_string.split_at(MAX_STR_SIZE as usize).0.to_string();
-            effects_string += "...";
-        }
-        mvwprintw(window, 1, 1, &format!("PowerUps: {effects_string}"));
-        wrefresh(window);
+    pub fn rival(canvas: &mut Canvas, status: &str) {
+        canvas.write((0, 2), &format!(" {status} "), Pen::new(Role::Hud));
+    }
+
+    pub fn toast(canvas: &mut Canvas, message: &str) {
+        let x = (canvas.cols() - message.len() as i32) / 2;
+        canvas.write((2, x), message, Pen::new(Role::Hud).with(A_BOLD()));
     }
 
     pub fn quit(score: i32) {
@@ -73,43 +58,136 @@ impl Printer {
         delwin(quit_window);
     }
 
-    pub fn objects<T>(win: WINDOW, objects: &[T], color: i16)
-    where
-        T: Object,
-    {
-        wattron(win, COLOR_PAIR(color));
-        for power in objects {
-            let pos = power.pos();
-            mvwaddch(win, pos.0, pos.1, power.char());
-        }
-        wattroff(win, COLOR_PAIR(color));
+    pub fn victory(score: i32) {
+        const LINES: i32 = 10;
+        const COLS: i32 = 20;
+
+        let victory_window = window::get_centralized_window(LINES, COLS);
+
+        box_(victory_window, 0, 0);
+        mvwaddstr(victory_window, 2, 5, "The Rival");
+        mvwaddstr(victory_window, 3, 8, "Got");
+        mvwaddstr(victory_window, 4, 5, "OUTLASTED!");
+        let score_str = format!("Score {}", score);
+        mvwaddstr(
+            victory_window,
+            7,
+            (COLS - score_str.len() as i32) / 2,
+            &score_str,
+        );
+        wgetch(victory_window);
+        delwin(victory_window);
     }
 
-    pub fn object<T>(win: WINDOW, object: &T)
-    where
-        T: Object,
-    {
-        let pos = object.pos();
-        wattron(win, COLOR_PAIR(object.color()));
-        mvwaddch(win, pos.0, pos.1, object.char());
-        wattroff(win, COLOR_PAIR(object.color()));
+    pub fn confirm(question: &str) -> bool {
+        const LINES: i32 = 5;
+        let cols = question.len() as i32 + 4;
+
+        let confirm_window = window::get_centralized_window(LINES, cols);
+
+        box_(confirm_window, 0, 0);
+        mvwaddstr(confirm_window, 1, 2, question);
+        mvwaddstr(confirm_window, 3, (cols - 5) / 2, "[y/N]");
+        let answer = wgetch(confirm_window);
+        delwin(confirm_window);
+        answer == 'y' as i32 || answer == 'Y' as i32
     }
 
-    pub fn shooters(win: WINDOW, enemies: &[Shooter]) {
-        for enemy in enemies {
-            Printer::shooter(win, enemy);
+    // Lets the player pick an option with the arrows and Enter, returning its index
+    pub fn menu(title: &str, options: &[&str]) -> usize {
+        let width = options.iter().map(|option| option.len()).max().unwrap_or(0);
+        let cols = width.max(title.len()) as i32 + 8;
+        let lines = options.len() as i32 + 4;
+
+        let menu_window = window::get_centralized_window(lines, cols);
+        keypad(menu_window, true);
+
+        let mut selected = 0;
+        loop {
+            box_(menu_window, 0, 0);
+            wattron(menu_window, A_BOLD());
+            mvwaddstr(menu_window, 1, (cols - title.len() as i32) / 2, title);
+            wattroff(menu_window, A_BOLD());
+            for (i, option) in options.iter().enumerate() {
+                let attributes = if i == selected {
+                    A_REVERSE()
+                } else {
+                    A_NORMAL()
+                };
+                wattron(menu_window, attributes);
+                mvwaddstr(menu_window, i as i32 + 3, (cols - width as i32) / 2, option);
+                wattroff(menu_window, attributes);
+            }
+            match wgetch(menu_window) {
+                KEY_UP => selected = (selected + options.len() - 1) % options.len(),
+                KEY_DOWN => selected = (selected + 1) % options.len(),
+                KEY_ENTER => break,
+                key if key == '\n' as i32 || key == ' ' as i32 => break,
+                _ => {}
+            }
         }
+        delwin(menu_window);
+        selected
     }
 
-    pub fn shooter(win: WINDOW, shooter: &Shooter) {
-        let color = shooter.color();
-        let pos = shooter.pos();
-        wattron(win, COLOR_PAIR(color));
-        mvwaddch(win, pos.0, pos.1, shooter.char());
-        wattroff(win, COLOR_PAIR(color));
+    pub fn help(keymap: &Keymap) {
+        const LINES: i32 = 24;
+        const COLS: i32 = 60;
 
-        for bullet in shooter.bullets() {
-            Printer::object(win, bullet);
+        let lines = LINES.min(getmaxy(stdscr()));
+        let cols = COLS.min(getmaxx(stdscr()));
+        let help_window = window::get_centralized_window(lines, cols);
+        keypad(help_window, true);
+
+        let text = help::lines(cols as usize - 4, keymap);
+        let rows = (lines - 4).max(1) as usize;
+        let last = text.len().saturating_sub(rows);
+        let mut top = 0;
+        loop {
+            werase(help_window);
+            box_(help_window, 0, 0);
+            mvwaddstr(help_window, 0, 2, " HELP ");
+            for (row, line) in text.iter().skip(top).take(rows).enumerate() {
+                mvwaddstr(help_window, row as i32 + 2, 2, line);
+            }
+            let footer = format!(
+                " {}/{} - q to close ",
+                (top + rows).min(text.len()),
+                text.len()
+            );
+            mvwaddstr(
+                help_window,
+                lines - 1,
+                cols - footer.len() as i32 - 2,
+                &footer,
+            );
+            match wgetch(help_window) {
+                KEY_UP => top = top.saturating_sub(1),
+                KEY_DOWN => top = (top + 1).min(last),
+                KEY_PPAGE => top = top.saturating_sub(rows),
+                KEY_NPAGE => top = (top + rows).min(last),
+                key if key == 'q' as i32 || key == 27 => break,
+                _ => {}
+            }
+        }
+        delwin(help_window);
+    }
+
+    fn sprite(canvas: &mut Canvas, pos: (i32, i32), sprite: &Sprite, pen: Pen, frame: usize) {
+        let glyph = match theme::glyph(sprite, frame) {
+            Some(glyph) => glyph.into(),
+            None => sprite.frame(frame),
+        };
+        for (row, line) in glyph.lines().enumerate() {
+            canvas.write((pos.0 + row as i32, pos.1), line, pen);
         }
     }
+
+    pub fn object<T>(canvas: &mut Canvas, object: &T, frame: usize)
+    where
+        T: Object,
+    {
+        let pen = Pen::new(object.color());
+        Printer::sprite(canvas, object.pos(), &object.sprite(), pen, frame);
+    }
 }
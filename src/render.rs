@@ -0,0 +1,233 @@
+use crate::{
+    canvas::{Canvas, Screen},
+    hud::Hud,
+    journal::{Journal, Panel},
+    printer::Printer,
+};
+use ncurses::{doupdate, WINDOW};
+use space_invaders::logic::Logic;
+
+// Everything that goes into a single frame of the game
+pub struct Scene<'a> {
+    pub score: i32,
+    pub lives: i8,
+    pub level: i32,
+    pub frame: usize,
+    pub logic: &'a Logic,
+    pub journal: &'a Journal,
+    pub rival: Option<String>,
+    pub toast: Option<String>,
+}
+
+pub trait Renderer {
+    fn render(&mut self, scene: &Scene);
+    // Called after a menu or dialog covered the game
+    fn invalidate(&mut self);
+}
+
+pub fn game(canvas: &mut Canvas, scene: &Scene) {
+    let (logic, frame) = (scene.logic, scene.frame);
+    canvas.border();
+    Printer::header(scene.score, canvas, scene.lives);
+    // Newer entities go over older ones
+    for entity in logic.entities().iter() {
+        Printer::object(canvas, entity, frame);
+    }
+    // Flashes go over everything else, without hiding anything for long
+    for flash in logic.flashes() {
+        Printer::object(canvas, flash, frame);
+    }
+    if let Some(rival) = &scene.rival {
+        Printer::rival(canvas, rival);
+    }
+    if let Some(toast) = &scene.toast {
+        Printer::toast(canvas, toast);
+    }
+}
+
+// Draws the game window and the HUD below it, then puts both on the terminal at once
+pub struct Terminal {
+    screen: Screen,
+    hud: Hud,
+    panel: Panel,
+}
+
+impl Terminal {
+    pub fn new(window: WINDOW) -> Self {
+        Self {
+            screen: Screen::new(window),
+            hud: Hud::new(window),
+            panel: Panel::new(window),
+        }
+    }
+}
+
+impl Renderer for Terminal {
+    fn render(&mut self, scene: &Scene) {
+        let mut canvas = self.screen.canvas();
+        game(&mut canvas, scene);
+        self.screen.present(canvas);
+        self.hud.draw(scene);
+        self.panel.draw(scene.journal);
+        doupdate();
+    }
+
+    fn invalidate(&mut self) {
+        self.screen.touch();
+        self.hud.touch();
+        self.panel.touch();
+    }
+}
+
+// Keeps the last frame as plain text, with the role of every cell mapped out below it
+#[cfg(test)]
+pub struct Text {
+    game: Canvas,
+    hud: Canvas,
+}
+
+#[cfg(test)]
+impl Text {
+    pub fn new(rows: i32, cols: i32) -> Self {
+        Self {
+            game: Canvas::new(rows, cols),
+            hud: Canvas::new(crate::hud::HUD_ROWS, cols),
+        }
+    }
+
+    pub fn text(&self) -> String {
+        let canvases = [&self.game, &self.hud];
+        let mut text = String::new();
+        for canvas in canvases {
+            for row in 0..canvas.rows() {
+                for col in 0..canvas.cols() {
+                    text += &canvas.cell((row, col)).glyph;
+                }
+                text += "\n";
+            }
+        }
+        text += "\n";
+        for canvas in canvases {
+            for row in 0..canvas.rows() {
+                for col in 0..canvas.cols() {
+                    text.push(code(canvas.cell((row, col)).pen.role));
+                }
+                text += "\n";
+            }
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+impl Renderer for Text {
+    fn render(&mut self, scene: &Scene) {
+        self.game = Canvas::new(self.game.rows(), self.game.cols());
+        game(&mut self.game, scene);
+        self.hud = Canvas::new(self.hud.rows(), self.hud.cols());
+        Hud::paint(&mut self.hud, scene);
+    }
+
+    fn invalidate(&mut self) {}
+}
+
+#[cfg(test)]
+fn code(role: Option<space_invaders::role::Role>) -> char {
+    use space_invaders::role::Role;
+    match role {
+        None => '.',
+        Some(Role::Laser) => 'l',
+        Some(Role::Enemy) => 'e',
+        Some(Role::Player) => 'p',
+        Some(Role::Bullet) => 'b',
+        Some(Role::Power) => 'w',
+        Some(Role::Shield) => 's',
+        Some(Role::Obstacle) => 'o',
+        Some(Role::Boss) => 'B',
+        Some(Role::Ultra) => 'u',
+        Some(Role::Explosion) => 'x',
+        Some(Role::Hud) => 'h',
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Renderer, Scene, Text};
+    use crate::journal::Journal;
+    use space_invaders::{garbage::Garbage, logic::Logic, COLS, LINES};
+    use std::{env, fs, path::PathBuf};
+
+    // Compares against tests/snapshots/<name>.txt, which UPDATE_SNAPSHOTS=1 rewrites
+    fn assert_snapshot(name: &str, scene: &Scene) {
+        let mut renderer = Text::new(LINES, COLS);
+        renderer.render(scene);
+        let actual = renderer.text();
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/snapshots")
+            .join(format!("{name}.txt"));
+        if env::var_os("UPDATE_SNAPSHOTS").is_some() {
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, &actual).unwrap();
+            return;
+        }
+        let expected = fs::read_to_string(&path)
+            .unwrap_or_else(|_| panic!("missing {}, run with UPDATE_SNAPSHOTS=1", path.display()));
+        assert!(
+            actual == expected,
+            "{name} doesn't match its snapshot\n--- expected\n{expected}\n--- actual\n{actual}"
+        );
+    }
+
+    fn scene<'a>(logic: &'a Logic, journal: &'a Journal, level: i32) -> Scene<'a> {
+        Scene {
+            score: 0,
+            lives: 3,
+            level,
+            frame: 0,
+            logic,
+            journal,
+            rival: None,
+            toast: None,
+        }
+    }
+
+    #[test]
+    fn first_wave() {
+        let mut logic = Logic::new(LINES, COLS).unwrap();
+        let journal = Journal::default();
+        let mut level = 0;
+        logic.level_up(&mut level);
+        assert_snapshot("first_wave", &scene(&logic, &journal, level));
+    }
+
+    #[test]
+    fn second_animation_frame() {
+        let mut logic = Logic::new(LINES, COLS).unwrap();
+        let journal = Journal::default();
+        let mut level = 0;
+        logic.level_up(&mut level);
+        let scene = Scene {
+            score: 1280,
+            frame: 1,
+            rival: Some("RIVAL 900 L1 **".to_string()),
+            toast: Some("Achievement: Pacifist".to_string()),
+            ..scene(&logic, &journal, level)
+        };
+        assert_snapshot("second_animation_frame", &scene);
+    }
+
+    #[test]
+    fn garbage_on_the_last_ship() {
+        let mut logic = Logic::new(LINES, COLS).unwrap();
+        let journal = Journal::default();
+        let mut level = 0;
+        logic.level_up(&mut level);
+        logic.receive(&Garbage::Enemies);
+        logic.receive(&Garbage::Jump);
+        let scene = Scene {
+            lives: 0,
+            ..scene(&logic, &journal, level)
+        };
+        assert_snapshot("garbage_on_the_last_ship", &scene);
+    }
+}
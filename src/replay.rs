@@ -0,0 +1,131 @@
+use crate::{
+    cli::Options,
+    controller::{Action, Keyboard, PlayerController},
+    session::Session,
+};
+use serde::{Deserialize, Serialize};
+use space_invaders::{logic::Logic, script::Scripts, Difficulty, Error};
+use std::{collections::VecDeque, fs, path::Path};
+
+// Everything a run starts from, which together with the moves made on each tick is
+// enough to play it out again exactly
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Setup {
+    pub seed: u64,
+    pub level: i32,
+    pub lives: i8,
+    pub size: (i32, i32),
+    pub difficulty: Difficulty,
+    // Where the scripts were loaded from, which are read again on playback
+    #[serde(default)]
+    pub scripts: Option<String>,
+}
+
+impl Setup {
+    // The seed is always settled on up front, so that any run can be recorded
+    pub fn new(options: &Options) -> Self {
+        Self {
+            seed: options.seed.unwrap_or_else(rand::random),
+            level: options.level,
+            lives: options.lives,
+            size: options.size,
+            difficulty: options.difficulty,
+            scripts: options.scripts.clone(),
+        }
+    }
+
+    pub fn scripts(&self) -> Result<Scripts, Error> {
+        match &self.scripts {
+            Some(dir) => Scripts::load(Path::new(dir)),
+            None => Ok(Scripts::default()),
+        }
+    }
+
+    pub fn start(&self, scripts: Scripts) -> Result<Session, Error> {
+        let (rows, cols) = self.size;
+        let gate = Logic::new(rows, cols)?
+            .with_seed(self.seed)
+            .with_difficulty(self.difficulty);
+        let mut session = Session::new(gate, self.level, self.lives);
+        session.scripts = scripts;
+        Ok(session)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Replay {
+    pub setup: Setup,
+    // The moves made on each tick, in order. Keys that do nothing, pausing and quitting
+    // are left out, since they don't change the game
+    pub ticks: Vec<Vec<Action>>,
+}
+
+impl Replay {
+    pub fn new(setup: Setup) -> Self {
+        Self {
+            setup,
+            ticks: vec![],
+        }
+    }
+
+    pub fn record(&mut self, actions: &[Action]) {
+        let moves = actions
+            .iter()
+            .filter(|action| matches!(action, Action::Shoot | Action::Left | Action::Right))
+            .copied()
+            .collect();
+        self.ticks.push(moves);
+    }
+
+    pub fn load(path: &str) -> Result<Replay, Error> {
+        let invalid = |reason: String| Error::File {
+            path: path.to_string(),
+            reason,
+        };
+        let contents = fs::read_to_string(path).map_err(|e| invalid(e.to_string()))?;
+        serde_json::from_str(&contents).map_err(|e| invalid(e.to_string()))
+    }
+
+    pub fn store(&self, path: &str) -> Result<(), Error> {
+        let invalid = |reason: String| Error::File {
+            path: path.to_string(),
+            reason,
+        };
+        let contents = serde_json::to_string(self).map_err(|e| invalid(e.to_string()))?;
+        fs::write(path, contents).map_err(|e| invalid(e.to_string()))
+    }
+}
+
+// Makes the recorded moves, then hands over to the player once they run out. The keyboard,
+// if any, is still listened to for pausing and quitting throughout
+pub struct Playback {
+    keyboard: Option<Keyboard>,
+    ticks: VecDeque<Vec<Action>>,
+}
+
+impl Playback {
+    pub fn new(keyboard: Option<Keyboard>, replay: Replay) -> Self {
+        Self {
+            keyboard,
+            ticks: replay.ticks.into(),
+        }
+    }
+}
+
+impl PlayerController for Playback {
+    fn act(&mut self, logic: &Logic) -> Vec<Action> {
+        let keys = match &mut self.keyboard {
+            Some(keyboard) => keyboard.act(logic),
+            None => vec![],
+        };
+        let Some(mut moves) = self.ticks.pop_front() else {
+            return keys;
+        };
+        // Whatever else was pressed would throw the rest of the recording off
+        moves.extend(
+            keys.into_iter()
+                .filter(|key| matches!(key, Action::Pause | Action::Quit)),
+        );
+        moves
+    }
+}
@@ -0,0 +1,75 @@
+//! What each cell on screen stands for, as far as themes are concerned.
+
+use crate::sprite::Sprite;
+use serde::{Deserialize, Serialize};
+
+/// What a cell on screen is drawn as, which a theme picks the colors and glyphs for.
+///
+/// Every role has its own color pair, numbered after it.
+#[derive(Clone, Copy, Eq, Hash, PartialEq, Debug, Serialize, Deserialize)]
+pub enum Role {
+    /// Enemy lasers.
+    Laser = 1,
+    /// Members of the formation.
+    Enemy,
+    /// The player's ship.
+    Player,
+    /// The player's bullets.
+    Bullet,
+    /// Power-ups, in play or held.
+    Power,
+    /// The shield over the player.
+    Shield,
+    /// Obstacles blocking shots.
+    Obstacle,
+    /// The boss.
+    Boss,
+    /// The beam fired with [`Effect::Ultra`](crate::Effect::Ultra).
+    Ultra,
+    /// Flashes left by explosions.
+    Explosion,
+    /// The score, lives and everything else around the board.
+    Hud,
+}
+
+impl Role {
+    /// Every role, in color pair order.
+    pub const ALL: [Role; 11] = [
+        Role::Laser,
+        Role::Enemy,
+        Role::Player,
+        Role::Bullet,
+        Role::Power,
+        Role::Shield,
+        Role::Obstacle,
+        Role::Boss,
+        Role::Ultra,
+        Role::Explosion,
+        Role::Hud,
+    ];
+
+    /// The color pair the role is drawn with.
+    pub fn pair(&self) -> i16 {
+        *self as i16
+    }
+
+    /// What the role is called in theme files.
+    pub fn name(&self) -> String {
+        format!("{self:?}").to_lowercase()
+    }
+
+    /// The sprite a role's glyph stands in for, if it has one.
+    pub fn sprite(&self) -> Option<Sprite> {
+        match self {
+            Role::Laser => Some(Sprite::Laser),
+            Role::Enemy => Some(Sprite::Enemy),
+            Role::Player => Some(Sprite::Player),
+            Role::Bullet => Some(Sprite::Bullet),
+            Role::Shield => Some(Sprite::Shield),
+            Role::Obstacle => Some(Sprite::Obstacle),
+            Role::Boss => Some(Sprite::Boss),
+            Role::Ultra => Some(Sprite::Ultra),
+            Role::Power | Role::Explosion | Role::Hud => None,
+        }
+    }
+}
@@ -0,0 +1,47 @@
+use serde::{Deserialize, Serialize};
+use space_invaders::logic::Logic;
+use std::{env, fs, io, path::PathBuf};
+
+const SAVE_FILE: &str = "save.json";
+
+#[derive(Serialize, Deserialize)]
+pub struct Snapshot {
+    pub lives: i8,
+    pub max_lives: i8,
+    pub level: i32,
+    pub score: i32,
+    pub logic: Logic,
+}
+
+// Where runs and achievements are kept between games
+pub fn data_dir() -> Option<PathBuf> {
+    let base = match env::var_os("XDG_DATA_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(env::var_os("HOME")?).join(".local/share"),
+    };
+    Some(base.join("invaders"))
+}
+
+fn path() -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join(SAVE_FILE))
+}
+
+pub fn exists() -> bool {
+    path().is_some_and(|path| path.exists())
+}
+
+pub fn store(snapshot: &Snapshot) -> io::Result<()> {
+    let path = path().ok_or_else(|| io::Error::other("No data directory available"))?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, serde_json::to_string(snapshot)?)
+}
+
+// A run can only be resumed once, so the save is consumed even if it turns out to be corrupt
+pub fn restore() -> Option<Snapshot> {
+    let path = path()?;
+    let contents = fs::read_to_string(&path).ok();
+    fs::remove_file(&path).ok();
+    serde_json::from_str(&contents?).ok()
+}
@@ -0,0 +1,407 @@
+//! Power-ups and rules written in rhai, loaded from a folder of scripts.
+
+use crate::{
+    direction::Direction,
+    error::{Error, Result},
+    event::Event,
+    logic::{Logic, POWER_COOLDOWN},
+    object::Object,
+    power::Effect,
+};
+use rhai::{
+    Array, CallFnOptions, Dynamic, Engine, EvalAltResult, ImmutableString, Scope, AST, INT,
+};
+use std::{cell::RefCell, fs, path::Path, rc::Rc, time::Duration};
+
+const EXTENSION: &str = "rhai";
+// Caps on what a single hook may get up to, so that a runaway script can't freeze the game
+const MAX_OPERATIONS: u64 = 100_000;
+const MAX_CALL_LEVELS: usize = 32;
+const MAX_SIZE: usize = 10_000;
+const DIRECTIONS: [(&str, Direction); 8] = [
+    ("up", Direction::Up),
+    ("down", Direction::Down),
+    ("left", Direction::Left),
+    ("right", Direction::Right),
+    ("left_up", Direction::LeftUp),
+    ("right_up", Direction::RightUp),
+    ("left_down", Direction::LeftDown),
+    ("right_down", Direction::RightDown),
+];
+
+// What a hook asked of the game, carried out once the hook returns
+#[derive(Clone, Copy, Debug)]
+enum Command {
+    Shoot((i32, i32), Direction),
+    MoveEnemies(Direction),
+    Grant(Effect),
+}
+
+/// All a hook gets to see of the game, and the only way it has of changing it.
+///
+/// Hooks read a copy of the board taken right before they were called. What they ask
+/// for through it, from firing bullets to granting effects, only happens once they
+/// return, and through the same [`Logic`] methods as everything else.
+#[derive(Clone)]
+pub struct Game {
+    level: i32,
+    size: (i32, i32),
+    player: (i32, i32),
+    enemies: Vec<(i32, i32)>,
+    commands: Rc<RefCell<Vec<Command>>>,
+}
+
+impl Game {
+    fn new(logic: &Logic, level: i32) -> Self {
+        Self {
+            level,
+            size: logic.size(),
+            player: logic.player().pos(),
+            enemies: logic.enemies().map(Object::pos).collect(),
+            commands: Rc::default(),
+        }
+    }
+
+    fn shoot(
+        &mut self,
+        row: INT,
+        col: INT,
+        dir: &str,
+    ) -> std::result::Result<(), Box<EvalAltResult>> {
+        let pos = (coordinate(row)?, coordinate(col)?);
+        let command = Command::Shoot(pos, direction(dir)?);
+        self.commands.borrow_mut().push(command);
+        Ok(())
+    }
+
+    fn move_enemies(&mut self, dir: &str) -> std::result::Result<(), Box<EvalAltResult>> {
+        let command = Command::MoveEnemies(direction(dir)?);
+        self.commands.borrow_mut().push(command);
+        Ok(())
+    }
+
+    fn grant(&mut self, name: &str) -> std::result::Result<(), Box<EvalAltResult>> {
+        let effect = Effect::ALL
+            .into_iter()
+            .find(|effect| effect.to_string().eq_ignore_ascii_case(name))
+            .ok_or_else(|| format!("Unknown effect {name}"))?;
+        self.commands.borrow_mut().push(Command::Grant(effect));
+        Ok(())
+    }
+
+    fn enemies(&mut self) -> Array {
+        self.enemies
+            .iter()
+            .map(|(row, col)| {
+                Dynamic::from_array(vec![INT::from(*row).into(), INT::from(*col).into()])
+            })
+            .collect()
+    }
+}
+
+fn coordinate(value: INT) -> std::result::Result<i32, Box<EvalAltResult>> {
+    i32::try_from(value).map_err(|_| format!("{value} is off the board").into())
+}
+
+fn direction(name: &str) -> std::result::Result<Direction, Box<EvalAltResult>> {
+    DIRECTIONS
+        .iter()
+        .find(|(named, _)| *named == name)
+        .map(|(_, dir)| *dir)
+        .ok_or_else(|| format!("Unknown direction {name}").into())
+}
+
+// What makes a script a power-up, rather than something that runs throughout
+struct Power {
+    glyph: char,
+    odds: f32,
+    lasts: Duration,
+    // How long ago it was collected, while it is still going
+    active: Option<Duration>,
+}
+
+struct Script {
+    name: String,
+    ast: AST,
+    // Bound to `this` in every hook, so that scripts can keep track of things
+    state: Dynamic,
+    power: Option<Power>,
+    // A script that failed once is switched off for good
+    broken: bool,
+}
+
+impl Script {
+    fn load(engine: &Engine, name: String, source: &str) -> Result<Script> {
+        let fail = |reason: String| Error::Script {
+            name: name.clone(),
+            reason,
+        };
+        let ast = engine.compile(source).map_err(|e| fail(e.to_string()))?;
+        let mut scope = Scope::new();
+        engine
+            .run_ast_with_scope(&mut scope, &ast)
+            .map_err(|e| fail(e.to_string()))?;
+
+        let power = match scope.get_value::<ImmutableString>("GLYPH") {
+            Some(glyph) => {
+                let mut chars = glyph.chars();
+                let glyph = match (chars.next(), chars.next()) {
+                    (Some(glyph), None) => glyph,
+                    _ => return Err(fail("GLYPH takes a single character".to_string())),
+                };
+                let odds = number(&scope, "ODDS").unwrap_or(0.0);
+                if !(0.0..=1.0).contains(&odds) {
+                    return Err(fail("ODDS takes a chance between 0 and 1".to_string()));
+                }
+                let lasts = match number(&scope, "DURATION") {
+                    Some(seconds) => Duration::try_from_secs_f64(seconds)
+                        .map_err(|_| fail("DURATION takes a number of seconds".to_string()))?,
+                    None => POWER_COOLDOWN,
+                };
+                Some(Power {
+                    glyph,
+                    odds: odds as f32,
+                    lasts,
+                    active: None,
+                })
+            }
+            None => None,
+        };
+        Ok(Script {
+            name,
+            ast,
+            state: Dynamic::from_map(Default::default()),
+            power,
+            broken: false,
+        })
+    }
+
+    // Power-ups only answer to their hooks while they are going, other scripts always do
+    fn is_running(&self) -> bool {
+        self.power
+            .as_ref()
+            .is_none_or(|power| power.active.is_some_and(|time| time < power.lasts))
+    }
+
+    fn defines(&self, hook: &str) -> bool {
+        self.ast
+            .iter_functions()
+            .any(|function| function.name == hook)
+    }
+
+    // Calls `hook` if the script has it, with the game as the first argument, then does
+    // what it asked for
+    fn call(
+        &mut self,
+        engine: &Engine,
+        logic: &mut Logic,
+        level: i32,
+        hook: &str,
+        args: &[INT],
+    ) -> Result<()> {
+        if !self.defines(hook) {
+            return Ok(());
+        }
+        let game = Game::new(logic, level);
+        let commands = Rc::clone(&game.commands);
+        let mut call_args = vec![Dynamic::from(game)];
+        call_args.extend(args.iter().map(|arg| Dynamic::from(*arg)));
+        let options = CallFnOptions::new()
+            .eval_ast(false)
+            .bind_this_ptr(&mut self.state);
+        // Whatever the hook returns is of no use
+        let _ = engine
+            .call_fn_with_options::<Dynamic>(options, &mut Scope::new(), &self.ast, hook, call_args)
+            .map_err(|e| Error::Script {
+                name: self.name.clone(),
+                reason: e.to_string(),
+            })?;
+
+        for command in commands.take() {
+            match command {
+                Command::Shoot(pos, dir) => logic.shoot_from(pos, dir),
+                Command::MoveEnemies(dir) => logic.move_enemies(dir),
+                Command::Grant(effect) => logic.grant(effect),
+            }
+        }
+        Ok(())
+    }
+}
+
+fn number(scope: &Scope, name: &str) -> Option<f64> {
+    let value = scope.get(name)?;
+    value
+        .as_float()
+        .ok()
+        .or_else(|| value.as_int().ok().map(|int| int as f64))
+}
+
+/// Power-ups and level behaviors written in [Rhai], loaded from `.rhai` files without
+/// recompiling the game.
+///
+/// A script may define any of these hooks, each called with a [`Game`] first:
+///
+/// - `on_tick(game)` once every tick
+/// - `on_player_shoot(game)` whenever the player fires
+/// - `on_enemy_killed(game, row, col)` for every enemy taken out, wherever it was
+/// - `on_collect(game)` when the player shoots the script's power-up
+///
+/// Scripts setting a `GLYPH` constant are power-ups, dropped onto the board at the
+/// `ODDS` of each tick and going for `DURATION` seconds once collected, or as long as
+/// built-in effects by default. Their hooks other than `on_collect` only run while they
+/// are going. Other scripts, such as ones shaping levels, run all the time.
+///
+/// Through the game, hooks can read `level`, `rows`, `cols`, `player_row`, `player_col`
+/// and `enemies`, as a list of `[row, col]`, then `shoot(row, col, dir)`,
+/// `move_enemies(dir)` and `grant(effect)`, where directions are written like `up` or
+/// `left_down` and effects by name. Each hook keeps whatever it likes in `this`, a map
+/// that lasts as long as the script is loaded.
+///
+/// Nothing else is within reach, neither files, nor the network, nor printing, and a hook
+/// that runs for too long fails. A script that fails is switched off for the rest of
+/// the run.
+///
+/// [Rhai]: https://rhai.rs
+pub struct Scripts {
+    engine: Engine,
+    scripts: Vec<Script>,
+}
+
+impl Default for Scripts {
+    fn default() -> Self {
+        let mut engine = Engine::new();
+        engine
+            .set_max_operations(MAX_OPERATIONS)
+            .set_max_call_levels(MAX_CALL_LEVELS)
+            .set_max_string_size(MAX_SIZE)
+            .set_max_array_size(MAX_SIZE)
+            .set_max_map_size(MAX_SIZE)
+            .on_print(|_| {})
+            .on_debug(|_, _, _| {});
+        engine
+            .register_type_with_name::<Game>("Game")
+            .register_get("level", |game: &mut Game| INT::from(game.level))
+            .register_get("rows", |game: &mut Game| INT::from(game.size.0))
+            .register_get("cols", |game: &mut Game| INT::from(game.size.1))
+            .register_get("player_row", |game: &mut Game| INT::from(game.player.0))
+            .register_get("player_col", |game: &mut Game| INT::from(game.player.1))
+            .register_get("enemies", Game::enemies)
+            .register_fn("shoot", Game::shoot)
+            .register_fn("move_enemies", Game::move_enemies)
+            .register_fn("grant", Game::grant);
+        Self {
+            engine,
+            scripts: vec![],
+        }
+    }
+}
+
+impl Scripts {
+    /// Every `.rhai` file in `dir`, in order of their names.
+    pub fn load(dir: &Path) -> Result<Scripts> {
+        let unreadable = |path: &Path, e: std::io::Error| Error::File {
+            path: path.display().to_string(),
+            reason: e.to_string(),
+        };
+        let mut paths = vec![];
+        for entry in fs::read_dir(dir).map_err(|e| unreadable(dir, e))? {
+            let path = entry.map_err(|e| unreadable(dir, e))?.path();
+            if path
+                .extension()
+                .is_some_and(|extension| extension == EXTENSION)
+            {
+                paths.push(path);
+            }
+        }
+        paths.sort();
+
+        let mut scripts = Scripts::default();
+        for path in paths {
+            let source = fs::read_to_string(&path).map_err(|e| unreadable(&path, e))?;
+            let name = path
+                .file_stem()
+                .map_or_else(String::new, |stem| stem.to_string_lossy().into_owned());
+            let script = Script::load(&scripts.engine, name, &source)?;
+            scripts.scripts.push(script);
+        }
+        Ok(scripts)
+    }
+
+    /// The name of the script behind a power-up of [`Kind::Custom`].
+    ///
+    /// [`Kind::Custom`]: crate::entity::Kind::Custom
+    pub fn name(&self, custom: u16) -> Option<&str> {
+        let script = self.scripts.get(usize::from(custom))?;
+        Some(&script.name)
+    }
+
+    /// Calls the hooks for whatever happened during the tick, to be run after
+    /// [`Logic::hit`] and before the events are reset, then drops the scripts' power-ups.
+    ///
+    /// Every script gets its turn even if one fails, in which case the first failure
+    /// is returned.
+    pub fn run(&mut self, logic: &mut Logic, level: i32) -> Result<()> {
+        let mut shots = 0;
+        let mut kills = vec![];
+        let mut collected = vec![];
+        for event in logic.events() {
+            match event {
+                Event::PlayerShot => shots += 1,
+                Event::EnemyKilled { pos, .. } => kills.push(*pos),
+                Event::CustomCollected(custom) => collected.push(*custom),
+                _ => {}
+            }
+        }
+
+        let mut failure = None;
+        for (custom, script) in self.scripts.iter_mut().enumerate() {
+            if script.broken {
+                continue;
+            }
+            let custom = custom as u16;
+            let result = (|| {
+                let power = script.power.as_mut();
+                if let Some(power) = power.filter(|_| collected.contains(&custom)) {
+                    power.active = Some(Duration::ZERO);
+                    script.call(&self.engine, logic, level, "on_collect", &[])?;
+                }
+                if !script.is_running() {
+                    return Ok(());
+                }
+                script.call(&self.engine, logic, level, "on_tick", &[])?;
+                for _ in 0..shots {
+                    script.call(&self.engine, logic, level, "on_player_shoot", &[])?;
+                }
+                for (row, col) in &kills {
+                    let args = [INT::from(*row), INT::from(*col)];
+                    script.call(&self.engine, logic, level, "on_enemy_killed", &args)?;
+                }
+                Ok(())
+            })();
+            if let Err(e) = result {
+                script.broken = true;
+                failure.get_or_insert(e);
+                continue;
+            }
+            if let Some(power) = &script.power {
+                if logic.roll(power.odds) {
+                    logic.drop_custom(custom, power.glyph);
+                }
+            }
+        }
+        failure.map_or(Ok(()), Err)
+    }
+
+    /// Lets `elapsed` go by for the power-ups that are going, like [`Logic::advance`].
+    pub fn advance(&mut self, elapsed: Duration) {
+        for script in &mut self.scripts {
+            if let Some(time) = script
+                .power
+                .as_mut()
+                .and_then(|power| power.active.as_mut())
+            {
+                *time += elapsed;
+            }
+        }
+    }
+}
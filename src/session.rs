@@ -0,0 +1,248 @@
+use crate::{controller::Action, save::Snapshot};
+use space_invaders::{
+    direction::Direction, error::Result, event::Event, logic::Logic, script::Scripts,
+};
+use std::time::Duration;
+
+// The world advances in fixed steps of this long, however fast input arrives
+pub const REFRESH_RATE: Duration = Duration::from_millis(50);
+
+// A run from the first wave to the last ship, ticking along without a terminal, so that
+// the game and headless simulations play by the very same rules
+pub struct Session {
+    pub lives: i8,
+    pub level: i32,
+    pub score: i32,
+    pub gate: Logic,
+    pub ticks: usize,
+    pub scripts: Scripts,
+    // Clearing a wave gives a ship back, up to this many
+    max_lives: i8,
+    // Quitting leaves the ships alone, so that the run can be saved as it was
+    quit: bool,
+}
+
+impl Session {
+    // Starts on the given level, with as many ships as it can ever have
+    pub fn new(mut gate: Logic, level: i32, lives: i8) -> Self {
+        let mut previous = level - 1;
+        gate.level_up(&mut previous);
+        // Skipping ahead doesn't count as clearing the waves in between
+        gate.events_reset();
+        gate.feats_reset();
+        Self {
+            lives,
+            level: previous,
+            score: 0,
+            gate,
+            ticks: 0,
+            scripts: Scripts::default(),
+            max_lives: lives,
+            quit: false,
+        }
+    }
+
+    // Picks up a saved run where it left off, with the cap on ships it was started with
+    pub fn resume(snapshot: Snapshot) -> Self {
+        Self {
+            lives: snapshot.lives,
+            level: snapshot.level,
+            score: snapshot.score,
+            gate: snapshot.logic,
+            ticks: 0,
+            scripts: Scripts::default(),
+            max_lives: snapshot.max_lives,
+            quit: false,
+        }
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            lives: self.lives,
+            max_lives: self.max_lives,
+            level: self.level,
+            score: self.score,
+            logic: self.gate.clone(),
+        }
+    }
+
+    pub fn next_wave(&mut self) {
+        if self.gate.enemies().next().is_none() {
+            self.gate.level_up(&mut self.level);
+            if self.lives < self.max_lives {
+                self.lives += 1;
+            }
+        }
+    }
+
+    // Only moves and shots reach the board, pausing is up to whoever runs the session
+    pub fn play(&mut self, action: Action) {
+        match action {
+            Action::Shoot => self.gate.player_shoot(),
+            Action::Right => self.gate.player_move(&Direction::Right),
+            Action::Left => self.gate.player_move(&Direction::Left),
+            Action::Quit => self.quit = true,
+            Action::Pause | Action::Idle => {}
+        }
+    }
+
+    // Moves everything along, leaving the tick's events and feats to be read. A failing
+    // script is reported, though the tick goes on all the same
+    pub fn tick(&mut self) -> Result<()> {
+        self.gate.fade();
+        self.gate.generate();
+        self.gate.shift();
+        self.gate.handle();
+        self.gate.hit();
+        let scripts = self.scripts.run(&mut self.gate, self.level);
+        for event in self.gate.events() {
+            match event {
+                Event::PlayerHit { .. } => self.lives -= 1,
+                Event::FormationLanded => self.lives = -1,
+                _ => {}
+            }
+            self.score += event.points(self.level);
+        }
+        scripts
+    }
+
+    pub fn end_tick(&mut self) {
+        self.gate.events_reset();
+        self.gate.feats_reset();
+        self.gate.advance(REFRESH_RATE);
+        self.scripts.advance(REFRESH_RATE);
+        self.ticks += 1;
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.lives <= -1
+    }
+
+    pub fn has_quit(&self) -> bool {
+        self.quit
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Session;
+    use crate::{controller::Action, save::Snapshot};
+    use proptest::prelude::*;
+    use space_invaders::{
+        event::Event, garbage::Garbage, logic::Logic, power::Effect, COLS, LINES,
+    };
+
+    const LIVES: i8 = 3;
+
+    fn action() -> impl Strategy<Value = Option<Action>> {
+        prop_oneof![
+            4 => Just(Some(Action::Idle)),
+            4 => Just(Some(Action::Left)),
+            4 => Just(Some(Action::Right)),
+            4 => Just(Some(Action::Shoot)),
+            // A power-up granted instead
+            1 => Just(None),
+        ]
+    }
+
+    #[test]
+    fn quitting_saves_the_run_as_it_was() {
+        let logic = Logic::new(LINES, COLS).unwrap().with_seed(0);
+        let mut session = Session::new(logic, 3, 2);
+        session.play(Action::Quit);
+        session.tick().unwrap();
+        session.end_tick();
+        assert!(session.has_quit());
+        assert!(!session.is_over());
+
+        let saved = serde_json::to_string(&session.snapshot()).unwrap();
+        let snapshot: Snapshot = serde_json::from_str(&saved).unwrap();
+        let resumed = Session::resume(snapshot);
+        assert_eq!(resumed.lives, 2);
+        assert_eq!(resumed.max_lives, 2);
+        assert_eq!(resumed.level, 3);
+        assert!(!resumed.is_over());
+        assert!(!resumed.has_quit());
+    }
+
+    #[test]
+    fn resumed_runs_play_out_the_same() {
+        let logic = Logic::new(LINES, COLS).unwrap().with_seed(0);
+        let mut session = Session::new(logic, 1, LIVES);
+        let step = |session: &mut Session, tick: usize| {
+            session.play([Action::Shoot, Action::Left, Action::Shoot, Action::Right][tick % 4]);
+            session.tick().unwrap();
+            session.end_tick();
+        };
+        for tick in 0..100 {
+            step(&mut session, tick);
+        }
+        session.gate.receive(&Garbage::Slowdown);
+
+        let saved = serde_json::to_string(&session.snapshot()).unwrap();
+        let mut resumed = Session::resume(serde_json::from_str(&saved).unwrap());
+        for tick in 100..400 {
+            step(&mut session, tick);
+            step(&mut resumed, tick);
+            assert_eq!(resumed.score, session.score);
+            assert_eq!(resumed.lives, session.lives);
+            assert_eq!(resumed.gate.combo(), session.gate.combo());
+            assert_eq!(resumed.gate.effect_timers(), session.gate.effect_timers());
+            assert!(resumed
+                .gate
+                .entities()
+                .iter()
+                .eq(session.gate.entities().iter()));
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(16))]
+
+        // Ships only go down to hits and a landed formation, and come back with cleared
+        // waves, so the count at the end is whatever those events add up to
+        #[test]
+        fn lives_only_change_through_events(
+            seed: u64,
+            script in prop::collection::vec(action(), 1..1000),
+            effects in prop::collection::vec(prop::sample::select(Effect::ALL.to_vec()), 1..50),
+        ) {
+            let logic = Logic::new(LINES, COLS).unwrap().with_seed(seed);
+            let mut session = Session::new(logic, 1, LIVES);
+            let mut effects = effects.into_iter().cycle();
+            let mut lives = LIVES;
+            for action in script {
+                match action {
+                    Some(action) => session.play(action),
+                    None => session.gate.grant(effects.next().unwrap()),
+                }
+                let before = session.lives;
+                session.tick().unwrap();
+                let events = session.gate.events();
+                if events.contains(&Event::FormationLanded) {
+                    prop_assert_eq!(session.lives, -1);
+                    lives = -1;
+                } else {
+                    let hits = events
+                        .iter()
+                        .filter(|event| matches!(event, Event::PlayerHit { .. }))
+                        .count() as i8;
+                    prop_assert_eq!(session.lives, before - hits);
+                    lives -= hits;
+                }
+                session.end_tick();
+                if session.is_over() {
+                    break;
+                }
+                let level = session.level;
+                session.next_wave();
+                if session.level > level {
+                    lives = (lives + 1).min(LIVES);
+                }
+                prop_assert_eq!(session.lives, lives);
+            }
+            prop_assert_eq!(session.lives, lives);
+            prop_assert_eq!(session.is_over(), lives <= -1);
+        }
+    }
+}
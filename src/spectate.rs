@@ -0,0 +1,199 @@
+use crate::{
+    canvas::{Canvas, Cell, Pen, Screen},
+    hud::{Hud, HUD_ROWS},
+    render::{self, Scene},
+};
+use ncurses::*;
+use space_invaders::{role::Role, Error};
+use std::{
+    io::{self, ErrorKind, Read, Write},
+    net::{TcpListener, TcpStream, ToSocketAddrs},
+    time::Duration,
+};
+
+// Spectators that can't keep up are dropped rather than slowing the game down
+const WRITE_TIMEOUT: Duration = Duration::from_millis(100);
+
+// Frames are the game with the HUD below it, sent as their size followed by every cell
+// row by row. Each cell carries its role, attributes and glyph, so that spectators draw
+// it with their own theme
+pub struct Broadcast {
+    listener: TcpListener,
+    spectators: Vec<TcpStream>,
+}
+
+impl Broadcast {
+    pub fn new(port: u16) -> io::Result<Self> {
+        let listener = TcpListener::bind(("0.0.0.0", port))?;
+        listener.set_nonblocking(true)?;
+        Ok(Self {
+            listener,
+            spectators: vec![],
+        })
+    }
+
+    fn accept(&mut self) {
+        while let Ok((stream, _)) = self.listener.accept() {
+            let ready = stream
+                .set_nonblocking(false)
+                .and_then(|_| stream.set_nodelay(true))
+                .and_then(|_| stream.set_write_timeout(Some(WRITE_TIMEOUT)));
+            if ready.is_ok() {
+                self.spectators.push(stream);
+            }
+        }
+    }
+
+    pub fn publish(&mut self, scene: &Scene) {
+        self.accept();
+        if self.spectators.is_empty() {
+            return;
+        }
+        let frame = encode(&capture(scene));
+        self.spectators
+            .retain_mut(|spectator| spectator.write_all(&frame).is_ok());
+    }
+}
+
+// The whole HUD, however little of it fits on the player's own terminal
+fn capture(scene: &Scene) -> Canvas {
+    let (rows, cols) = scene.logic.size();
+    let mut game = Canvas::new(rows, cols);
+    render::game(&mut game, scene);
+    let mut hud = Canvas::new(HUD_ROWS, cols);
+    Hud::paint(&mut hud, scene);
+    let mut frame = Canvas::new(rows + HUD_ROWS, cols);
+    frame.paste(&game, 0);
+    frame.paste(&hud, rows);
+    frame
+}
+
+fn encode(canvas: &Canvas) -> Vec<u8> {
+    let mut frame = vec![];
+    frame.extend_from_slice(&(canvas.rows() as u16).to_le_bytes());
+    frame.extend_from_slice(&(canvas.cols() as u16).to_le_bytes());
+    for row in 0..canvas.rows() {
+        for col in 0..canvas.cols() {
+            let cell = canvas.cell((row, col));
+            frame.push(cell.pen.role.map_or(0, |role| role.pair() as u8));
+            frame.extend_from_slice(&cell.pen.attributes.to_le_bytes());
+            frame.push(cell.glyph.len() as u8);
+            frame.extend_from_slice(cell.glyph.as_bytes());
+        }
+    }
+    frame
+}
+
+fn decode(stream: &mut impl Read) -> io::Result<Canvas> {
+    let rows = read_size(stream)?;
+    let cols = read_size(stream)?;
+    let mut canvas = Canvas::new(rows, cols);
+    for row in 0..rows {
+        for col in 0..cols {
+            canvas.put((row, col), read_cell(stream)?);
+        }
+    }
+    Ok(canvas)
+}
+
+fn read_size(stream: &mut impl Read) -> io::Result<i32> {
+    let mut size = [0; 2];
+    stream.read_exact(&mut size)?;
+    Ok(u16::from_le_bytes(size) as i32)
+}
+
+fn read_cell(stream: &mut impl Read) -> io::Result<Cell> {
+    let mut head = [0; 6];
+    stream.read_exact(&mut head)?;
+    let role = Role::ALL
+        .into_iter()
+        .find(|role| role.pair() == head[0] as i16);
+    let attributes = attr_t::from_le_bytes([head[1], head[2], head[3], head[4]]);
+    let mut glyph = vec![0; head[5] as usize];
+    stream.read_exact(&mut glyph)?;
+    let glyph = String::from_utf8(glyph).map_err(|e| io::Error::new(ErrorKind::InvalidData, e))?;
+    Ok(Cell {
+        glyph,
+        pen: Pen { role, attributes },
+    })
+}
+
+pub struct Spectator {
+    stream: TcpStream,
+    window: Option<(WINDOW, Screen)>,
+}
+
+impl Spectator {
+    pub fn connect<A: ToSocketAddrs>(addr: A) -> io::Result<Self> {
+        Ok(Self {
+            stream: TcpStream::connect(addr)?,
+            window: None,
+        })
+    }
+
+    fn window(&mut self, lines: i32, cols: i32) -> Result<(WINDOW, &mut Screen), Error> {
+        let fits = self
+            .window
+            .as_ref()
+            .is_some_and(|(win, _)| getmaxy(*win) == lines && getmaxx(*win) == cols);
+        if !fits {
+            if let Some((win, _)) = self.window.take() {
+                delwin(win);
+            }
+            clear();
+            refresh();
+            crate::window::fits(lines, cols)?;
+            let win = crate::window::get_centralized_window(lines, cols);
+            wtimeout(win, 0);
+            self.window = Some((win, Screen::new(win)));
+        }
+        let (win, screen) = self.window.as_mut().expect("The window was just made");
+        Ok((*win, screen))
+    }
+
+    fn render(&mut self) -> Result<WINDOW, Error> {
+        let canvas = decode(&mut self.stream)?;
+        let (win, screen) = self.window(canvas.rows(), canvas.cols())?;
+        screen.present(canvas);
+        doupdate();
+        Ok(win)
+    }
+
+    // Renders frames until the game ends or the spectator presses 'q'
+    pub fn watch(&mut self) -> Result<(), Error> {
+        loop {
+            let win = match self.render() {
+                Ok(win) => win,
+                Err(Error::Io(e)) if e.kind() == ErrorKind::UnexpectedEof => return Ok(()),
+                Err(e) => return Err(e),
+            };
+            if wgetch(win) == 'q' as i32 {
+                return Ok(());
+            }
+        }
+    }
+}
+
+impl Drop for Spectator {
+    fn drop(&mut self) {
+        if let Some((win, _)) = self.window.take() {
+            delwin(win);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode, encode};
+    use crate::canvas::{Canvas, Pen};
+    use space_invaders::role::Role;
+
+    #[test]
+    fn frames_arrive_as_they_were_drawn() {
+        let mut canvas = Canvas::new(3, 6);
+        canvas.border();
+        canvas.write((1, 1), "a👾", Pen::new(Role::Enemy));
+        let frame = encode(&canvas);
+        assert_eq!(decode(&mut frame.as_slice()).unwrap(), canvas);
+    }
+}
@@ -0,0 +1,61 @@
+//! What things on the board look like.
+
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use unicode_width::UnicodeWidthStr;
+
+/// What an [`Object`](crate::Object) looks like, as the glyphs of its two animation frames.
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub enum Sprite {
+    /// The player's ship.
+    Player,
+    /// A member of the formation.
+    Enemy,
+    /// The boss crossing the top of the board.
+    Boss,
+    /// A shot fired by an enemy.
+    Laser,
+    /// A shot fired by the player.
+    Bullet,
+    /// A cell of the shield raised over the player.
+    Shield,
+    /// A cell of an obstacle blocking shots.
+    Obstacle,
+    /// A cell of the wide beam fired with [`Effect::Ultra`](crate::Effect::Ultra).
+    Ultra,
+    /// A single character that doesn't animate.
+    Glyph(char),
+}
+
+impl Sprite {
+    /// The glyphs of one of the sprite's frames, one line per row.
+    ///
+    /// Sprites alternate between two frames. Both must cover the same cells, since
+    /// collisions are derived from them.
+    pub fn frame(&self, frame: usize) -> Cow<'static, str> {
+        let frames = match self {
+            Sprite::Player => ["▲", "▲"],
+            Sprite::Enemy => ["▼", "▽"],
+            Sprite::Boss => ["◀■▶", "◁□▷"],
+            Sprite::Laser => ["¦", "¦"],
+            Sprite::Bullet => ["•", "•"],
+            Sprite::Shield => ["▂", "▂"],
+            Sprite::Obstacle => [" ", " "],
+            Sprite::Ultra => [" ", " "],
+            Sprite::Glyph(char) => return Cow::Owned(char.to_string()),
+        };
+        Cow::Borrowed(frames[frame % frames.len()])
+    }
+
+    /// Rows and columns covered by the sprite, where wide glyphs take two columns.
+    pub fn extent(&self) -> (i32, i32) {
+        Sprite::extent_of(&self.frame(0))
+    }
+
+    /// Rows and columns covered by any frame.
+    pub fn extent_of(frame: &str) -> (i32, i32) {
+        let rows = frame.lines().count() as i32;
+        let cols = frame.lines().map(|line| line.width()).max().unwrap_or(0) as i32;
+        (rows, cols)
+    }
+}
@@ -0,0 +1,529 @@
+use ncurses::{
+    attr_t, has_colors, init_extended_pair, A_BLINK, A_BOLD, A_DIM, A_NORMAL, A_REVERSE,
+    A_STANDOUT, A_UNDERLINE, COLORS, COLOR_PAIR, COLOR_PAIRS,
+};
+use space_invaders::{role::Role, sprite::Sprite, Error};
+use std::{collections::HashMap, fmt, fs, sync::OnceLock};
+
+const BUILTIN_THEMES: [(&str, &str); 5] = [
+    ("default", include_str!("../themes/default.theme")),
+    (
+        "classic-green",
+        include_str!("../themes/classic-green.theme"),
+    ),
+    (
+        "high-contrast",
+        include_str!("../themes/high-contrast.theme"),
+    ),
+    ("colorblind", include_str!("../themes/colorblind.theme")),
+    ("monochrome", include_str!("../themes/monochrome.theme")),
+];
+// Approximations of the basic colors as most terminals show them
+const BASIC_COLORS: [(u8, u8, u8); 8] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+];
+// The levels of each channel in the 6x6x6 color cube of 256 color terminals
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+static THEME: OnceLock<Theme> = OnceLock::new();
+
+// How many colors the terminal can show, from none at all to any RGB value
+#[derive(Clone, Copy, PartialEq, PartialOrd, Debug)]
+enum Depth {
+    Monochrome,
+    Basic,
+    Extended,
+    Direct,
+}
+
+impl Depth {
+    fn detect() -> Depth {
+        let pairs = Role::ALL.len() as i32;
+        if !has_colors() || COLOR_PAIRS() <= pairs || COLORS() < 8 {
+            Depth::Monochrome
+        } else if COLORS() >= 1 << 24 {
+            Depth::Direct
+        } else if COLORS() >= 256 {
+            Depth::Extended
+        } else {
+            Depth::Basic
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Color {
+    Default,
+    Indexed(u8),
+    Rgb(u8, u8, u8),
+}
+
+impl Color {
+    fn depth(&self) -> Depth {
+        match self {
+            Color::Default => Depth::Basic,
+            Color::Indexed(0..=7) => Depth::Basic,
+            Color::Indexed(_) => Depth::Extended,
+            Color::Rgb(..) => Depth::Direct,
+        }
+    }
+
+    fn rgb(&self) -> (u8, u8, u8) {
+        match *self {
+            Color::Default => BASIC_COLORS[7],
+            Color::Indexed(index @ 0..=7) => BASIC_COLORS[index as usize],
+            Color::Indexed(index @ 8..=15) => BASIC_COLORS[index as usize - 8],
+            Color::Indexed(index @ 16..=231) => {
+                let index = index as usize - 16;
+                (
+                    CUBE_LEVELS[index / 36],
+                    CUBE_LEVELS[index / 6 % 6],
+                    CUBE_LEVELS[index % 6],
+                )
+            }
+            Color::Indexed(index) => {
+                let gray = 8 + 10 * (index - 232);
+                (gray, gray, gray)
+            }
+            Color::Rgb(r, g, b) => (r, g, b),
+        }
+    }
+
+    // The color number the terminal understands, approximating whatever it can't show
+    fn number(&self, depth: Depth) -> i32 {
+        match (*self, depth) {
+            (Color::Default, _) => -1,
+            (Color::Indexed(index @ 0..=7), _) => index as i32,
+            // Direct color terminals keep the first colors for the basic ones
+            (_, Depth::Direct) => {
+                let (r, g, b) = self.rgb();
+                ((r as i32) << 16) | ((g as i32) << 8) | b as i32
+            }
+            (Color::Indexed(index), Depth::Extended) => index as i32,
+            (Color::Rgb(r, g, b), Depth::Extended) => nearest_extended((r, g, b)),
+            _ => nearest(self.rgb(), BASIC_COLORS.into_iter()),
+        }
+    }
+}
+
+fn distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> i32 {
+    let channel = |a: u8, b: u8| (a as i32 - b as i32).pow(2);
+    channel(a.0, b.0) + channel(a.1, b.1) + channel(a.2, b.2)
+}
+
+fn nearest(rgb: (u8, u8, u8), palette: impl Iterator<Item = (u8, u8, u8)>) -> i32 {
+    palette
+        .enumerate()
+        .min_by_key(|(_, color)| distance(rgb, *color))
+        .map_or(-1, |(index, _)| index as i32)
+}
+
+fn nearest_extended(rgb: (u8, u8, u8)) -> i32 {
+    let level = |channel: u8| nearest((channel, 0, 0), CUBE_LEVELS.iter().map(|l| (*l, 0, 0)));
+    let cube = 16 + 36 * level(rgb.0) + 6 * level(rgb.1) + level(rgb.2);
+    let gray = 232 + nearest(rgb, (0..24).map(|i| (8 + 10 * i, 8 + 10 * i, 8 + 10 * i)));
+    [cube, gray]
+        .into_iter()
+        .min_by_key(|index| distance(rgb, Color::Indexed(*index as u8).rgb()))
+        .unwrap_or(cube)
+}
+
+// Picks the richest of the alternatives the terminal can show, or approximates the first one
+fn resolve(colors: &[Color], depth: Depth) -> i32 {
+    colors
+        .iter()
+        .rev()
+        .find(|color| color.depth() <= depth)
+        .unwrap_or(&colors[0])
+        .number(depth)
+}
+
+#[derive(Debug)]
+pub struct ThemeError {
+    line: usize,
+    message: String,
+}
+
+impl fmt::Display for ThemeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+struct Style {
+    glyph: Option<Vec<String>>,
+    fg: Vec<Color>,
+    bg: Vec<Color>,
+    attributes: attr_t,
+}
+
+pub struct Theme {
+    // As it was asked for, a built-in name or a path
+    name: String,
+    styles: HashMap<Role, Style>,
+    depth: Depth,
+}
+
+impl Theme {
+    pub fn builtin(name: &str) -> Option<Theme> {
+        let (_, contents) = BUILTIN_THEMES
+            .iter()
+            .find(|(builtin, _)| *builtin == name)?;
+        let theme = Theme::parse(contents).expect("Built-in themes are valid");
+        Some(theme.named(name))
+    }
+
+    pub fn builtin_names() -> Vec<&'static str> {
+        BUILTIN_THEMES.iter().map(|(name, _)| *name).collect()
+    }
+
+    // Either the name of a built-in theme or the path to a theme file
+    pub fn load(name: &str) -> Result<Theme, Error> {
+        if let Some(theme) = Theme::builtin(name) {
+            return Ok(theme);
+        }
+        let error = |reason: String| Error::Theme {
+            name: name.to_string(),
+            reason,
+        };
+        let contents = fs::read_to_string(name).map_err(|e| error(e.to_string()))?;
+        let theme = Theme::parse(&contents).map_err(|e| error(e.to_string()))?;
+        Ok(theme.named(name))
+    }
+
+    fn named(self, name: &str) -> Theme {
+        Theme {
+            name: name.to_string(),
+            ..self
+        }
+    }
+
+    pub fn parse(contents: &str) -> Result<Theme, ThemeError> {
+        let mut styles = HashMap::new();
+        for (index, line) in contents.lines().enumerate() {
+            let error = |message: String| ThemeError {
+                line: index + 1,
+                message,
+            };
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (name, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+            let role = Role::ALL
+                .into_iter()
+                .find(|role| role.name() == name)
+                .ok_or_else(|| error(format!("unknown role '{name}'")))?;
+            let (glyph, rest) = parse_glyph(rest.trim_start()).map_err(error)?;
+            if let Some(frames) = &glyph {
+                check_glyph(&role, frames).map_err(error)?;
+            }
+            let mut words = rest.split_whitespace();
+            let fg = parse_color(words.next()).map_err(error)?;
+            let bg = parse_color(words.next()).map_err(error)?;
+            let mut attributes = A_NORMAL();
+            for word in words {
+                attributes |= parse_attribute(word).map_err(error)?;
+            }
+            let style = Style {
+                glyph,
+                fg,
+                bg,
+                attributes,
+            };
+            if styles.insert(role, style).is_some() {
+                return Err(error(format!("'{name}' is styled twice")));
+            }
+        }
+        if let Some(role) = Role::ALL.iter().find(|role| !styles.contains_key(role)) {
+            return Err(ThemeError {
+                line: contents.lines().count(),
+                message: format!("'{}' is missing", role.name()),
+            });
+        }
+        Ok(Theme {
+            name: String::new(),
+            styles,
+            depth: Depth::Basic,
+        })
+    }
+
+    // Sets up the color pairs and makes this the theme everything is drawn with.
+    // Terminals without enough colors get the monochrome theme instead, as does
+    // anyone asking for it. It has to be installed before anything is drawn, which
+    // would settle on the default theme for good
+    pub fn install(self, monochrome: bool) -> Result<(), Error> {
+        let depth = if monochrome {
+            Depth::Monochrome
+        } else {
+            Depth::detect()
+        };
+        let name = self.name.clone();
+        let mut theme = match depth {
+            Depth::Monochrome => Theme::builtin("monochrome").expect("It is built in"),
+            _ => self,
+        };
+        theme.depth = depth;
+        THEME.set(theme).map_err(|_| Error::Theme {
+            name,
+            reason: "a theme was already in use".to_string(),
+        })?;
+        let theme = current();
+        if theme.depth == Depth::Monochrome {
+            return Ok(());
+        }
+        for (role, style) in &theme.styles {
+            let fg = resolve(&style.fg, theme.depth);
+            let bg = resolve(&style.bg, theme.depth);
+            init_extended_pair(role.pair() as i32, fg, bg);
+        }
+        Ok(())
+    }
+}
+
+fn current() -> &'static Theme {
+    THEME.get_or_init(|| Theme::builtin("default").expect("The default theme is built in"))
+}
+
+pub fn attributes(role: Role) -> attr_t {
+    let theme = current();
+    let attributes = theme.styles[&role].attributes;
+    match theme.depth {
+        Depth::Monochrome => attributes,
+        _ => COLOR_PAIR(role.pair()) | attributes,
+    }
+}
+
+pub fn glyph(sprite: &Sprite, frame: usize) -> Option<&'static str> {
+    let role = Role::ALL
+        .into_iter()
+        .find(|role| role.sprite() == Some(*sprite))?;
+    let frames = current().styles[&role].glyph.as_ref()?;
+    Some(&frames[frame % frames.len()])
+}
+
+fn parse_glyph(text: &str) -> Result<(Option<Vec<String>>, &str), String> {
+    if let Some(rest) = text.strip_prefix('-') {
+        return Ok((None, rest));
+    }
+    let Some(quoted) = text.strip_prefix('"') else {
+        return Err("expected a quoted glyph or '-'".to_string());
+    };
+    let Some((glyph, rest)) = quoted.split_once('"') else {
+        return Err("unterminated glyph".to_string());
+    };
+    let frames = glyph.split('|').map(str::to_string).collect();
+    Ok((Some(frames), rest))
+}
+
+fn check_glyph(role: &Role, frames: &[String]) -> Result<(), String> {
+    let name = role.name();
+    let Some(sprite) = role.sprite() else {
+        return Err(format!("'{name}' can't have a glyph"));
+    };
+    if frames.len() > 2 {
+        return Err(format!("'{name}' has more than two frames"));
+    }
+    for frame in frames {
+        if Sprite::extent_of(frame) != sprite.extent() {
+            return Err(format!(
+                "'{name}' glyph must cover the same cells as its sprite"
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn parse_color(word: Option<&str>) -> Result<Vec<Color>, String> {
+    let Some(word) = word else {
+        return Err("expected a foreground and a background color".to_string());
+    };
+    word.split('/').map(parse_alternative).collect()
+}
+
+fn parse_alternative(word: &str) -> Result<Color, String> {
+    let color = match word {
+        "default" => Color::Default,
+        "black" => Color::Indexed(0),
+        "red" => Color::Indexed(1),
+        "green" => Color::Indexed(2),
+        "yellow" => Color::Indexed(3),
+        "blue" => Color::Indexed(4),
+        "magenta" => Color::Indexed(5),
+        "cyan" => Color::Indexed(6),
+        "white" => Color::Indexed(7),
+        _ => {
+            if let Some(hex) = word.strip_prefix('#').filter(|hex| hex.len() == 6) {
+                let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16);
+                match (channel(0), channel(2), channel(4)) {
+                    (Ok(r), Ok(g), Ok(b)) => Color::Rgb(r, g, b),
+                    _ => return Err(format!("unknown color '{word}'")),
+                }
+            } else {
+                match word.parse() {
+                    Ok(index) => Color::Indexed(index),
+                    _ => return Err(format!("unknown color '{word}'")),
+                }
+            }
+        }
+    };
+    Ok(color)
+}
+
+fn parse_attribute(word: &str) -> Result<attr_t, String> {
+    let attribute = match word {
+        "bold" => A_BOLD(),
+        "dim" => A_DIM(),
+        "underline" => A_UNDERLINE(),
+        "reverse" => A_REVERSE(),
+        "blink" => A_BLINK(),
+        "standout" => A_STANDOUT(),
+        _ => return Err(format!("unknown attribute '{word}'")),
+    };
+    Ok(attribute)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{nearest_extended, resolve, Color, Depth, Theme};
+    use ncurses::{A_BOLD, A_NORMAL};
+    use space_invaders::role::Role;
+
+    const THEME: &str = "
+        # A comment, then every role
+        laser      \"!\"      red/203/#ff5f5f  default
+        enemy      \"W|w\"    green            default
+        player     -        yellow/221       default  bold
+        bullet     -        blue             default
+        power      -        magenta          default
+        shield     -        cyan             default
+        obstacle   -        white            white
+        boss       \"<O>\"    black/240        default
+        ultra      -        blue             blue
+        explosion  -        yellow           default
+        hud        -        default          default
+    ";
+
+    // The theme with one line swapped for another
+    fn with(line: usize, replacement: &str) -> String {
+        let mut lines: Vec<&str> = THEME.lines().collect();
+        lines[line] = replacement;
+        lines.join("\n")
+    }
+
+    fn error(contents: &str) -> String {
+        Theme::parse(contents)
+            .err()
+            .expect("The theme is malformed")
+            .to_string()
+    }
+
+    #[test]
+    fn themes_style_every_role() {
+        let theme = Theme::parse(THEME).unwrap();
+        let laser = &theme.styles[&Role::Laser];
+        assert_eq!(laser.glyph, Some(vec!["!".to_string()]));
+        assert_eq!(
+            laser.fg,
+            [
+                Color::Indexed(1),
+                Color::Indexed(203),
+                Color::Rgb(255, 95, 95)
+            ]
+        );
+        assert_eq!(laser.bg, [Color::Default]);
+        assert_eq!(laser.attributes, A_NORMAL());
+        let enemy = &theme.styles[&Role::Enemy];
+        assert_eq!(enemy.glyph, Some(vec!["W".to_string(), "w".to_string()]));
+        let player = &theme.styles[&Role::Player];
+        assert_eq!(player.glyph, None);
+        assert_eq!(player.attributes, A_NORMAL() | A_BOLD());
+        for name in Theme::builtin_names() {
+            assert!(Theme::builtin(name).is_some());
+        }
+    }
+
+    #[test]
+    fn malformed_lines_are_reported_where_they_are() {
+        assert_eq!(
+            error(&with(2, "lazer - red default")),
+            "line 3: unknown role 'lazer'"
+        );
+        assert_eq!(
+            error(&with(2, "laser \"! red default")),
+            "line 3: unterminated glyph"
+        );
+        assert_eq!(
+            error(&with(2, "laser ! red default")),
+            "line 3: expected a quoted glyph or '-'"
+        );
+        assert_eq!(
+            error(&with(2, "laser \"!!\" red default")),
+            "line 3: 'laser' glyph must cover the same cells as its sprite"
+        );
+        assert_eq!(
+            error(&with(2, "laser \"!|!|!\" red default")),
+            "line 3: 'laser' has more than two frames"
+        );
+        assert_eq!(
+            error(&with(6, "power \"P\" magenta default")),
+            "line 7: 'power' can't have a glyph"
+        );
+        assert_eq!(
+            error(&with(2, "laser - #ff5f5 default")),
+            "line 3: unknown color '#ff5f5'"
+        );
+        assert_eq!(
+            error(&with(2, "laser - red/256 default")),
+            "line 3: unknown color '256'"
+        );
+        assert_eq!(
+            error(&with(2, "laser - red")),
+            "line 3: expected a foreground and a background color"
+        );
+        assert_eq!(
+            error(&with(2, "laser - red default shiny")),
+            "line 3: unknown attribute 'shiny'"
+        );
+        assert_eq!(
+            error(&with(3, "laser - red default")),
+            "line 4: 'laser' is styled twice"
+        );
+        assert_eq!(error(&with(12, "")), "line 14: 'hud' is missing");
+    }
+
+    #[test]
+    fn colors_fall_back_to_what_the_terminal_shows() {
+        let red = [
+            Color::Indexed(1),
+            Color::Indexed(203),
+            Color::Rgb(255, 95, 95),
+        ];
+        assert_eq!(resolve(&red, Depth::Monochrome), 1);
+        assert_eq!(resolve(&red, Depth::Basic), 1);
+        assert_eq!(resolve(&red, Depth::Extended), 203);
+        assert_eq!(resolve(&red, Depth::Direct), 0xff5f5f);
+        assert_eq!(resolve(&[Color::Default], Depth::Direct), -1);
+
+        // Without a basic alternative, the first one is approximated
+        let orange = [Color::Rgb(255, 135, 0)];
+        assert_eq!(resolve(&orange, Depth::Extended), 208);
+        assert_eq!(resolve(&orange, Depth::Basic), 3);
+        assert_eq!(resolve(&[Color::Indexed(196)], Depth::Basic), 1);
+        // Bright colors keep to their basic counterparts
+        assert_eq!(resolve(&[Color::Indexed(12)], Depth::Basic), 4);
+
+        assert_eq!(nearest_extended((255, 0, 0)), 196);
+        assert_eq!(nearest_extended((0, 0, 0)), 16);
+        // Grays closer to the gray ramp than to the cube
+        assert_eq!(nearest_extended((128, 128, 128)), 244);
+        assert_eq!(nearest_extended((120, 121, 119)), 243);
+    }
+}
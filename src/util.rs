@@ -1,14 +1,14 @@
-use crate::{direction::Direction, COLS, LINES};
-use rand::{distributions::Uniform, prelude::Distribution};
+use crate::direction::Direction;
+use rand::{distributions::Uniform, prelude::Distribution, Rng};
 
-pub fn out_of_bounds(pos: (i32, i32)) -> bool {
-    pos.0 <= 1 || pos.1 <= 0 || pos.0 >= LINES - 1 || pos.1 >= COLS - 1
+// Whether the cell is on the border or the header of a board of `size` rows and columns
+pub fn out_of_bounds(pos: (i32, i32), size: (i32, i32)) -> bool {
+    pos.0 <= 1 || pos.1 <= 0 || pos.0 >= size.0 - 1 || pos.1 >= size.1 - 1
 }
 
-pub fn random_event(odds: f32) -> bool {
+pub fn random_event(rng: &mut impl Rng, odds: f32) -> bool {
     let step = Uniform::new(0., 1.);
-    let mut rng = rand::thread_rng();
-    let choice = step.sample(&mut rng);
+    let choice = step.sample(rng);
     choice <= odds
 }
 
@@ -0,0 +1,227 @@
+use serde::{Deserialize, Serialize};
+use space_invaders::{garbage::Garbage, logic::Logic};
+use std::{
+    io::{self, ErrorKind, Read, Write},
+    net::{TcpListener, TcpStream, ToSocketAddrs},
+    time::Duration,
+};
+
+// Every this many kills, a piece of garbage is sent to the opponent
+const GARBAGE_KILLS: i32 = 5;
+const POLL_TIMEOUT: Duration = Duration::from_millis(1);
+// A peer that takes longer than this to take a message counts as gone, rather than
+// freezing the game
+const WRITE_TIMEOUT: Duration = Duration::from_millis(100);
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Status {
+    pub score: i32,
+    pub lives: i8,
+    pub level: i32,
+}
+
+// Each message is sent as a single line of JSON
+#[derive(Serialize, Deserialize)]
+enum Message {
+    Status(Status),
+    Garbage(Garbage),
+    Over,
+}
+
+pub struct Versus {
+    stream: TcpStream,
+    buffer: Vec<u8>,
+    kills: i32,
+    sent: Option<Status>,
+    opponent: Option<Status>,
+    is_connected: bool,
+    has_opponent_lost: bool,
+}
+
+impl Versus {
+    pub fn host(port: u16) -> io::Result<Self> {
+        Versus::accept(&TcpListener::bind(("0.0.0.0", port))?)
+    }
+
+    // Waits for the opponent to connect
+    fn accept(listener: &TcpListener) -> io::Result<Self> {
+        let (stream, _) = listener.accept()?;
+        Versus::new(stream)
+    }
+
+    pub fn join<A: ToSocketAddrs>(addr: A) -> io::Result<Self> {
+        Versus::new(TcpStream::connect(addr)?)
+    }
+
+    fn new(stream: TcpStream) -> io::Result<Self> {
+        stream.set_nodelay(true)?;
+        stream.set_read_timeout(Some(POLL_TIMEOUT))?;
+        stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+        Ok(Self {
+            stream,
+            buffer: vec![],
+            kills: 0,
+            sent: None,
+            opponent: None,
+            is_connected: true,
+            has_opponent_lost: false,
+        })
+    }
+
+    fn send(&mut self, message: &Message) {
+        if !self.is_connected {
+            return;
+        }
+        let mut line = serde_json::to_vec(message).expect("Messages are always serializable");
+        line.push(b'\n');
+        if self.stream.write_all(&line).is_err() {
+            self.is_connected = false;
+        }
+    }
+
+    fn receive(&mut self) -> Vec<Message> {
+        let mut chunk = [0; 1024];
+        while self.is_connected {
+            match self.stream.read(&mut chunk) {
+                Ok(0) => self.is_connected = false,
+                Ok(size) => self.buffer.extend_from_slice(&chunk[..size]),
+                Err(e) if e.kind() == ErrorKind::WouldBlock || e.kind() == ErrorKind::TimedOut => {
+                    break
+                }
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(_) => self.is_connected = false,
+            }
+        }
+
+        let mut messages = vec![];
+        while let Some(end) = self.buffer.iter().position(|byte| *byte == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=end).collect();
+            // A peer speaking anything else is as good as gone
+            match serde_json::from_slice(&line) {
+                Ok(message) => messages.push(message),
+                Err(_) => self.is_connected = false,
+            }
+        }
+        messages
+    }
+
+    pub fn sync(&mut self, logic: &mut Logic, status: Status) {
+        self.kills += logic.events().iter().filter(|event| event.scores()).count() as i32;
+        while self.kills >= GARBAGE_KILLS {
+            self.kills -= GARBAGE_KILLS;
+            let garbage = logic.garbage();
+            self.send(&Message::Garbage(garbage));
+        }
+
+        if self.sent != Some(status) {
+            self.send(&Message::Status(status));
+            self.sent = Some(status);
+        }
+
+        for message in self.receive() {
+            match message {
+                Message::Status(status) => self.opponent = Some(status),
+                Message::Garbage(garbage) => logic.receive(&garbage),
+                Message::Over => self.has_opponent_lost = true,
+            }
+        }
+    }
+
+    pub fn lose(&mut self) {
+        self.send(&Message::Over);
+    }
+
+    pub fn opponent(&self) -> Option<&Status> {
+        self.opponent.as_ref()
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.is_connected
+    }
+
+    pub fn has_opponent_lost(&self) -> bool {
+        self.has_opponent_lost
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Message, Status, Versus};
+    use space_invaders::{garbage::Garbage, logic::Logic, power::Effect, COLS, LINES};
+    use std::{
+        net::TcpListener,
+        thread,
+        time::{Duration, Instant},
+    };
+
+    const STATUS: Status = Status {
+        score: 120,
+        lives: 2,
+        level: 3,
+    };
+
+    // Hosts on a free local port and joins it
+    fn pair() -> (Versus, Versus) {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let join = thread::spawn(move || Versus::join(("127.0.0.1", port)).unwrap());
+        let host = Versus::accept(&listener).unwrap();
+        (host, join.join().unwrap())
+    }
+
+    // Keeps syncing until `done` holds, as the messages take a moment to arrive
+    fn sync_until(versus: &mut Versus, logic: &mut Logic, done: impl Fn(&Versus) -> bool) {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !done(versus) {
+            assert!(Instant::now() < deadline, "Nothing arrived in time");
+            versus.sync(logic, STATUS);
+            thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    #[test]
+    fn peers_exchange_status_garbage_and_the_end() {
+        let (mut host, mut guest) = pair();
+        let mut logic = Logic::new(LINES, COLS).unwrap();
+
+        host.send(&Message::Garbage(Garbage::Jump));
+        host.sync(&mut logic, STATUS);
+        sync_until(&mut guest, &mut logic, |guest| guest.opponent().is_some());
+        assert!(guest.opponent() == Some(&STATUS));
+        let jumping = |logic: &Logic| {
+            logic
+                .effect_timers()
+                .iter()
+                .any(|(effect, _)| *effect == Effect::Jump)
+        };
+        assert!(jumping(&logic));
+
+        let mut other = Logic::new(LINES, COLS).unwrap();
+        sync_until(&mut host, &mut other, |host| host.opponent().is_some());
+        assert!(!jumping(&other));
+
+        guest.lose();
+        sync_until(&mut host, &mut other, Versus::has_opponent_lost);
+        assert!(host.is_connected());
+    }
+
+    #[test]
+    fn a_stalled_peer_disconnects() {
+        let (mut host, _guest) = pair();
+        // The guest never reads, so the host's writes pile up until they time out
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while host.is_connected() {
+            assert!(Instant::now() < deadline, "Writes never timed out");
+            host.send(&Message::Status(STATUS));
+        }
+    }
+
+    #[test]
+    fn a_dropped_peer_disconnects() {
+        let (mut host, guest) = pair();
+        let mut logic = Logic::new(LINES, COLS).unwrap();
+        drop(guest);
+        sync_until(&mut host, &mut logic, |host| !host.is_connected());
+        assert!(host.opponent().is_none());
+    }
+}
@@ -1,4 +1,19 @@
 use ncurses::*;
+use space_invaders::Error;
+
+// Whether the screen has room for a window that big, since ncurses refuses to create
+// windows hanging off it
+pub fn fits(lines: i32, cols: i32) -> Result<(), Error> {
+    let x = getmaxx(stdscr());
+    let y = getmaxy(stdscr());
+    if y < lines || x < cols {
+        return Err(Error::Terminal {
+            size: (y, x),
+            needed: (lines, cols),
+        });
+    }
+    Ok(())
+}
 
 pub fn get_centralized_window(lines: i32, cols: i32) -> WINDOW {
     let x = getmaxx(stdscr());
@@ -0,0 +1,188 @@
+//! Swaps in effect handlers of our own and checks that the rules call on them.
+
+use space_invaders::{
+    logic::{
+        effects::{EffectHandler, Shot},
+        POWER_COOLDOWN,
+    },
+    Direction, Effect, Kind, Logic, Object, Team, COLS, LINES,
+};
+use std::time::Duration;
+
+const TICK: Duration = Duration::from_millis(50);
+
+// Fires a second bullet to the side, and a parting one once it runs out
+struct Spread;
+
+impl EffectHandler for Spread {
+    fn modify_shot(&self, logic: &mut Logic, shot: &mut Shot) {
+        let (row, col) = logic.player().pos();
+        shot.bullets.push(((row - 1, col + 1), Direction::RightUp));
+    }
+
+    fn on_expire(&self, logic: &mut Logic) {
+        let (row, col) = logic.player().pos();
+        logic.shoot_from((row - 1, col), Direction::Up);
+    }
+}
+
+// Does nothing at all, so that only the effect's timer is left
+struct Inert;
+
+impl EffectHandler for Inert {}
+
+fn tick(logic: &mut Logic) {
+    logic.fade();
+    logic.generate();
+    logic.shift();
+    logic.handle();
+    logic.hit();
+    logic.events_reset();
+    logic.advance(TICK);
+}
+
+#[test]
+fn registered_handlers_replace_built_in_effects() {
+    let mut logic = Logic::new(LINES, COLS)
+        .unwrap()
+        .with_seed(0)
+        .with_handler(Effect::Grenade, Spread);
+    let bullets = |logic: &Logic| logic.entities().bullets(Team::Player).count();
+
+    logic.grant(Effect::Grenade);
+    // Long enough to reload, and for the effect to be going
+    for _ in 0..20 {
+        tick(&mut logic);
+    }
+    logic.player_shoot();
+    assert_eq!(bullets(&logic), 2);
+    assert!(logic
+        .entities()
+        .bullets(Team::Player)
+        .all(|bullet| !bullet.is_explosive()));
+
+    for _ in 0..LINES {
+        tick(&mut logic);
+    }
+    assert_eq!(bullets(&logic), 0);
+    logic.advance(POWER_COOLDOWN);
+    logic.handle();
+    assert_eq!(bullets(&logic), 1);
+}
+
+// A first wave with `effect` just granted, handled by `Inert` if `replaced`
+fn game(effect: Effect, replaced: bool) -> Logic {
+    let logic = Logic::new(LINES, COLS).unwrap().with_seed(0);
+    let mut logic = if replaced {
+        logic.with_handler(effect, Inert)
+    } else {
+        logic
+    };
+    let mut level = 0;
+    logic.level_up(&mut level);
+    logic.grant(effect);
+    logic
+}
+
+#[test]
+fn replaced_shields_never_go_up() {
+    for replaced in [false, true] {
+        let mut logic = game(Effect::Shield, replaced);
+        tick(&mut logic);
+        let shields = logic.entities().of(Kind::Shield).count();
+        assert_eq!(shields > 0, !replaced);
+    }
+}
+
+#[test]
+fn replaced_warp_stops_at_the_edge() {
+    for replaced in [false, true] {
+        let mut logic = game(Effect::Warp, replaced);
+        let mut wrapped = false;
+        for _ in 0..COLS {
+            let col = logic.player().pos().1;
+            logic.player_move(&Direction::Left);
+            wrapped |= logic.player().pos().1 > col;
+        }
+        assert_eq!(wrapped, !replaced);
+    }
+}
+
+#[test]
+fn replaced_hijack_lets_enemies_fire() {
+    for replaced in [false, true] {
+        let mut logic = game(Effect::Hijack, replaced);
+        let mut fired = false;
+        for _ in 0..100 {
+            tick(&mut logic);
+            fired |= logic.entities().bullets(Team::Enemy).next().is_some();
+        }
+        assert_eq!(fired, replaced);
+    }
+}
+
+#[test]
+fn replaced_jump_stays_down() {
+    for replaced in [false, true] {
+        let mut logic = game(Effect::Jump, replaced);
+        tick(&mut logic);
+        let row = if replaced { LINES - 2 } else { 3 };
+        assert_eq!(logic.player().pos().0, row);
+    }
+}
+
+#[test]
+fn replaced_mind_control_kills_instead() {
+    for replaced in [false, true] {
+        let mut logic = game(Effect::Mindcontrol, replaced);
+        logic.grant(Effect::Lock);
+        let enemies = logic.enemies().count();
+        logic.player_move(&Direction::Left);
+        logic.advance(POWER_COOLDOWN / 10);
+        logic.player_shoot();
+        for _ in 0..LINES {
+            tick(&mut logic);
+        }
+        let controlled = logic
+            .enemies()
+            .filter(|enemy| enemy.team() == Team::Player)
+            .count();
+        assert_eq!(controlled > 0, !replaced);
+        assert_eq!(logic.enemies().count() < enemies, replaced);
+    }
+}
+
+#[test]
+fn mind_controlled_shots_get_past_their_shooter() {
+    let mut logic = Logic::new(LINES, COLS).unwrap().with_seed(0);
+    let mut level = 0;
+    logic.level_up(&mut level);
+    // Hold the formation still, so that the shot finds the enemy right above the player
+    logic.grant(Effect::Lock);
+    logic.grant(Effect::Mindcontrol);
+    logic.player_move(&Direction::Left);
+    logic.advance(POWER_COOLDOWN / 10);
+    logic.player_shoot();
+    let controlled = |logic: &Logic| {
+        logic
+            .enemies()
+            .find(|enemy| enemy.team() == Team::Player)
+            .map(|enemy| enemy.id())
+    };
+    while controlled(&logic).is_none() {
+        tick(&mut logic);
+    }
+    let shooter = controlled(&logic).unwrap();
+
+    // Whatever it fires has to make it out of its cell on the tick it fires
+    let mut shots = 0;
+    for _ in 0..100 {
+        tick(&mut logic);
+        shots += logic
+            .entities()
+            .bullets(Team::Player)
+            .filter(|bullet| bullet.owner() == Some(shooter))
+            .count();
+    }
+    assert!(shots > 0);
+}
@@ -0,0 +1,165 @@
+//! Plays random scripts against a headless [`Logic`] and checks the rules it should
+//! never break, whatever the player does and whatever power-ups come their way.
+//!
+//! A failing script is shrunk down to the shortest one still breaking the rule, and
+//! since every run is seeded it replays the same way.
+
+use proptest::prelude::*;
+use space_invaders::{
+    event::{Cause, Event},
+    garbage::Garbage,
+    Direction, Effect, Kind, Logic, Object, COLS, LINES,
+};
+use std::time::Duration;
+
+// How long a tick lasts in the game
+const TICK: Duration = Duration::from_millis(50);
+
+#[derive(Clone, Debug)]
+enum Input {
+    Idle,
+    Left,
+    Right,
+    Shoot,
+    // As if the player had shot the power-up
+    Grant(Effect),
+    // Sent by a versus opponent
+    Garbage(Garbage),
+}
+
+fn input() -> impl Strategy<Value = Input> {
+    let garbage = prop_oneof![
+        Just(Garbage::Enemies),
+        Just(Garbage::Jump),
+        Just(Garbage::Slowdown),
+    ];
+    prop_oneof![
+        8 => Just(Input::Idle),
+        8 => Just(Input::Left),
+        8 => Just(Input::Right),
+        8 => Just(Input::Shoot),
+        2 => prop::sample::select(Effect::ALL.to_vec()).prop_map(Input::Grant),
+        1 => garbage.prop_map(Input::Garbage),
+    ]
+}
+
+fn active(logic: &Logic, effect: Effect) -> bool {
+    logic
+        .effect_timers()
+        .iter()
+        .any(|(active, _)| *active == effect)
+}
+
+fn enemies(logic: &Logic) -> usize {
+    logic.enemies().count()
+}
+
+// Everything that should hold between any two ticks
+fn check_board(logic: &Logic) -> Result<(), TestCaseError> {
+    prop_assert_eq!(logic.player().kind(), Kind::Player);
+    let mut previous = None;
+    for entity in logic.entities().iter() {
+        let (row, col) = entity.pos();
+        let (rows, cols) = entity.extent();
+        prop_assert!(
+            logic.on_board((row, col)) && logic.on_board((row + rows - 1, col + cols - 1)),
+            "{:?} is off the board",
+            entity
+        );
+        prop_assert!(previous < Some(entity.id()), "{:?} is out of order", entity);
+        previous = Some(entity.id());
+        if let Some(owner) = entity.owner() {
+            prop_assert!(
+                logic.entities().get(owner).is_some(),
+                "{:?} outlived whoever fired it",
+                entity
+            );
+        }
+    }
+    Ok(())
+}
+
+fn play(seed: u64, script: &[Input]) -> Result<(), TestCaseError> {
+    let mut logic = Logic::new(LINES, COLS)
+        .expect("The default board fits the first wave")
+        .with_seed(seed);
+    let mut level = 0;
+    logic.level_up(&mut level);
+    logic.events_reset();
+    check_board(&logic)?;
+
+    for input in script {
+        if logic.enemies().next().is_none() {
+            logic.level_up(&mut level);
+            check_board(&logic)?;
+        }
+
+        let mut reinforced = false;
+        match input {
+            Input::Idle => {}
+            Input::Left => logic.player_move(&Direction::Left),
+            Input::Right => logic.player_move(&Direction::Right),
+            Input::Shoot => logic.player_shoot(),
+            Input::Grant(effect) => logic.grant(*effect),
+            Input::Garbage(garbage) => {
+                reinforced = *garbage == Garbage::Enemies;
+                logic.receive(garbage);
+            }
+        }
+        let kamizake = active(&logic, Effect::Kamizake);
+        let before = enemies(&logic);
+
+        logic.fade();
+        logic.generate();
+        logic.shift();
+        logic.handle();
+        logic.hit();
+        check_board(&logic)?;
+
+        prop_assert!(
+            reinforced || enemies(&logic) <= before,
+            "The formation grew from {} to {} mid-wave",
+            before,
+            enemies(&logic)
+        );
+        // Effects only ever start between ticks or while collecting power-ups, which
+        // happens before the player can get hit, so whatever is active now was then
+        let invincible = active(&logic, Effect::Invincible);
+        for event in logic.events() {
+            match event {
+                Event::PlayerHit {
+                    cause: Cause::Laser,
+                } => prop_assert!(!invincible, "Lost a ship while invincible"),
+                Event::PlayerHit { cause } => {
+                    prop_assert!(kamizake, "Lost a ship to {:?} without Kamizake", cause)
+                }
+                _ => {}
+            }
+        }
+        let landed = logic.events().contains(&Event::FormationLanded);
+        logic.events_reset();
+        logic.feats_reset();
+        logic.advance(TICK);
+        if landed {
+            break;
+        }
+    }
+    Ok(())
+}
+
+// A quick run by default, PROPTEST_CASES asks for a longer one
+fn cases() -> u32 {
+    std::env::var("PROPTEST_CASES")
+        .ok()
+        .and_then(|cases| cases.parse().ok())
+        .unwrap_or(16)
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(cases()))]
+
+    #[test]
+    fn invariants_hold(seed: u64, script in prop::collection::vec(input(), 1..1000)) {
+        play(seed, &script)?;
+    }
+}
@@ -0,0 +1,132 @@
+//! Loads scripts from disk and checks that their hooks reach the game.
+
+use space_invaders::{script::Scripts, Error, Kind, Logic, Object, Team, COLS, LINES};
+use std::{fs, path::Path, time::Duration};
+
+const TICK: Duration = Duration::from_millis(50);
+
+fn scripts(name: &str, files: &[(&str, &str)]) -> Result<Scripts, Error> {
+    let dir = std::env::temp_dir().join(format!("space-invaders-{name}-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    for (file, source) in files {
+        fs::write(dir.join(file), source).unwrap();
+    }
+    let scripts = Scripts::load(&dir);
+    fs::remove_dir_all(&dir).unwrap();
+    scripts
+}
+
+fn logic() -> Logic {
+    let mut logic = Logic::new(LINES, COLS).unwrap().with_seed(0);
+    let mut level = 0;
+    logic.level_up(&mut level);
+    logic
+}
+
+fn tick(logic: &mut Logic, scripts: &mut Scripts) -> Result<(), Error> {
+    logic.fade();
+    logic.generate();
+    logic.shift();
+    logic.handle();
+    logic.hit();
+    let result = scripts.run(logic, 1);
+    logic.events_reset();
+    logic.advance(TICK);
+    scripts.advance(TICK);
+    result
+}
+
+#[test]
+fn bundled_scripts_load() {
+    Scripts::load(Path::new("scripts")).unwrap();
+}
+
+#[test]
+fn hooks_fire_bullets_and_keep_state() {
+    let source = "
+        fn on_player_shoot(game) {
+            this.shots = (this.shots ?? 0) + 1;
+            game.shoot(game.player_row - 1, 1, \"right_up\");
+            if this.shots == 2 { game.grant(\"Shield\"); }
+        }
+    ";
+    let mut scripts = scripts("hooks", &[("spray.rhai", source)]).unwrap();
+    let mut logic = logic();
+    let bullets = |logic: &Logic| logic.entities().bullets(Team::Player).count();
+
+    // Long enough to reload between shots
+    let reload = |logic: &mut Logic, scripts: &mut Scripts| {
+        for _ in 0..20 {
+            logic.advance(TICK);
+            scripts.advance(TICK);
+        }
+    };
+
+    reload(&mut logic, &mut scripts);
+    logic.player_shoot();
+    tick(&mut logic, &mut scripts).unwrap();
+    assert_eq!(bullets(&logic), 2);
+    assert!(logic.entities().of(Kind::Shield).next().is_none());
+
+    reload(&mut logic, &mut scripts);
+    logic.player_shoot();
+    tick(&mut logic, &mut scripts).unwrap();
+    tick(&mut logic, &mut scripts).unwrap();
+    assert!(logic.entities().of(Kind::Shield).next().is_some());
+}
+
+#[test]
+fn shots_from_off_the_board_start_on_it() {
+    let source = "
+        fn on_player_shoot(game) {
+            game.shoot(0, -5, \"down\");
+            game.shoot(1000, 1000, \"up\");
+        }
+    ";
+    let mut scripts = scripts("bounds", &[("wild.rhai", source)]).unwrap();
+    let mut logic = logic();
+    for _ in 0..20 {
+        logic.advance(TICK);
+    }
+    logic.player_shoot();
+    tick(&mut logic, &mut scripts).unwrap();
+
+    let shots: Vec<_> = logic
+        .entities()
+        .bullets(Team::Player)
+        .map(Object::pos)
+        .collect();
+    assert!(shots.contains(&(2, 1)));
+    assert!(shots.contains(&(LINES - 2, COLS - 2)));
+    assert!(shots.iter().all(|pos| logic.on_board(*pos)));
+}
+
+#[test]
+fn power_ups_drop_and_failures_switch_scripts_off() {
+    let power = "const GLYPH = \"M\"; const ODDS = 1.0;";
+    let broken = "fn on_tick(game) { game.move_enemies(\"sideways\"); }";
+    let mut scripts =
+        scripts("powers", &[("broken.rhai", broken), ("magnet.rhai", power)]).unwrap();
+    let mut logic = logic();
+
+    let failure = tick(&mut logic, &mut scripts);
+    assert!(matches!(failure, Err(Error::Script { name, .. }) if name == "broken"));
+    // Once broken, it stays quiet
+    tick(&mut logic, &mut scripts).unwrap();
+
+    let dropped = logic
+        .entities()
+        .iter()
+        .filter(|entity| entity.kind() == Kind::Custom(1))
+        .count();
+    assert_eq!(dropped, 2);
+    assert_eq!(scripts.name(1), Some("magnet"));
+}
+
+#[test]
+fn bad_scripts_fail_to_load() {
+    let glyph = scripts("glyph", &[("wide.rhai", "const GLYPH = \"MM\";")]);
+    assert!(matches!(glyph, Err(Error::Script { .. })));
+    let syntax = scripts("syntax", &[("typo.rhai", "fn on_tick(game {")]);
+    assert!(matches!(syntax, Err(Error::Script { .. })));
+}